@@ -1,7 +1,19 @@
 use bevy::prelude::*;
+use bevy::utils::HashMap;
 use bevy_prototype_lyon::prelude::*;
+use iyes_loopless::prelude::*;
 
-use crate::resources::CurrentEra;
+use crate::collisionsmod::collisions::check_collision;
+use crate::collisionsmod::components::{Collider, CollisionLayers, InEras};
+use crate::collisionsmod::grid::{CollidableQuery, CollisionGrid};
+use crate::events::{EraChangedEvent, TimeTravelBlockedEvent};
+use crate::levelmod::editor::Editing;
+use crate::levelmod::level::LevelEntity;
+use crate::playermod::hookshot::is_hookshot_firing;
+use crate::playermod::player::Player;
+use crate::playermod::rewind::is_rewinding;
+use crate::resources::{CurrentEra, Era, TickInput};
+use crate::systemsmod::tick::{GAMEPLAY_STAGE, TICK};
 use crate::{BACKGROUND_ONE, TILE_SIZE};
 
 pub struct BackgroundPlugin;
@@ -9,69 +21,189 @@ pub struct BackgroundPlugin;
 impl Plugin for BackgroundPlugin {
     fn build(&self, app: &mut App) {
         app.add_startup_system(draw_backgrounds)
-            .add_system(toggle_background);
+            .add_fixed_timestep_system(
+                TICK,
+                GAMEPLAY_STAGE,
+                travel_through_time
+                    .run_if_not(is_rewinding)
+                    .run_if_not(is_hookshot_firing)
+                    .label("timetravel"),
+            )
+            .add_system(show_current_era.run_on_event::<EraChangedEvent>())
+            .add_system(show_era_members)
+            .add_system(report_blocked_time_travel.run_on_event::<TimeTravelBlockedEvent>());
     }
 }
 
+const STARTING_ERA: Era = Era::Present;
+
 #[derive(Component)]
 struct BackgroundParent;
 
+/// The two checkerboard colours used for an era's background layer.
+fn era_colors(era: Era) -> (Color, Color) {
+    match era {
+        Era::Past => (Color::DARK_GREEN, Color::SEA_GREEN),
+        Era::Present => (Color::RED, Color::ORANGE),
+        Era::Future => (Color::MIDNIGHT_BLUE, Color::SILVER),
+    }
+}
+
 fn draw_backgrounds(mut commands: Commands) {
-    let background = draw_background_with_children(&mut commands, Color::RED);
-    commands.insert_resource(CurrentEra {
-        current_era: background,
-    });
+    let mut era_roots = HashMap::default();
+    for era in Era::ALL {
+        let background = draw_background_parent(&mut commands, era == STARTING_ERA);
+        era_roots.insert(era, background);
+    }
+    commands.insert_resource(CurrentEra::new(STARTING_ERA, era_roots));
 }
 
-fn draw_background_with_children(commands: &mut Commands, color: Color) -> Entity {
-    let shape = shapes::Rectangle {
-        extents: Vec2::new(TILE_SIZE, TILE_SIZE),
-        origin: RectangleOrigin::Center,
-    };
+fn draw_background_parent(commands: &mut Commands, is_visible: bool) -> Entity {
     commands
         .spawn((
-            SpatialBundle::from_transform(Transform::from_translation(Vec3::new(
-                0.0,
-                0.0,
-                BACKGROUND_ONE,
-            ))),
+            SpatialBundle {
+                visibility: Visibility { is_visible },
+                transform: Transform::from_translation(Vec3::new(0.0, 0.0, BACKGROUND_ONE)),
+                ..Default::default()
+            },
             BackgroundParent {},
         ))
-        .with_children(|parent| {
-            for i in -10..10 {
-                for j in -10..10 {
-                    let mut shape_color = Color::ORANGE;
-                    if (i + j) % 2 == 0 {
-                        shape_color = color;
-                    }
-                    parent.spawn(GeometryBuilder::build_as(
-                        &shape,
-                        DrawMode::Outlined {
-                            fill_mode: FillMode::color(shape_color),
-                            outline_mode: StrokeMode::new(Color::BLACK, TILE_SIZE / 10.0),
-                        },
-                        // Transform::default(),
-                        Transform::from_translation(Vec3::new(
-                            i as f32 * TILE_SIZE,
-                            j as f32 * TILE_SIZE,
-                            BACKGROUND_ONE,
-                        )),
-                    ));
-                }
-            }
-        })
         .id()
 }
 
-fn toggle_background(
-    mut query: Query<&mut Visibility>,
-    keyboard: Res<Input<KeyCode>>,
+/// Draws checkerboard floor tiles for a level as children of an era's background layer.
+pub fn draw_floor(
+    commands: &mut Commands,
+    era_root: Entity,
+    era: Era,
+    tiles: impl Iterator<Item = (i32, i32)>,
+) {
+    let shape = shapes::Rectangle {
+        extents: Vec2::new(TILE_SIZE, TILE_SIZE),
+        origin: RectangleOrigin::Center,
+    };
+    let (color, alternate_color) = era_colors(era);
+    commands.entity(era_root).with_children(|parent| {
+        for (i, j) in tiles {
+            let mut shape_color = alternate_color;
+            if (i + j) % 2 == 0 {
+                shape_color = color;
+            }
+            parent.spawn((
+                GeometryBuilder::build_as(
+                    &shape,
+                    DrawMode::Outlined {
+                        fill_mode: FillMode::color(shape_color),
+                        outline_mode: StrokeMode::new(Color::BLACK, TILE_SIZE / 10.0),
+                    },
+                    Transform::from_translation(Vec3::new(
+                        i as f32 * TILE_SIZE,
+                        j as f32 * TILE_SIZE,
+                        BACKGROUND_ONE,
+                    )),
+                ),
+                LevelEntity,
+            ));
+        }
+    });
+}
+
+/// Moves the player forward or backward one era, unless
+/// a collidable in the destination era occupies the
+/// player's position. The level editor can always
+/// switch eras. Nobody can while the hookshot is out,
+/// as it could pull them somewhere blocked in the new era.
+fn travel_through_time(
+    player_query: Query<(&Transform, Entity, &Collider, &CollisionLayers), With<Player>>,
+    collidable_query: Query<CollidableQuery>,
+    tick_input: Res<TickInput>,
+    editing: Res<Editing>,
+    grid: Res<CollisionGrid>,
+    mut current_era: ResMut<CurrentEra>,
+    mut era_event_writer: EventWriter<EraChangedEvent>,
+    mut blocked_event_writer: EventWriter<TimeTravelBlockedEvent>,
+) {
+    let destination_era = if tick_input.travel_forward {
+        current_era.current_era.next()
+    } else if tick_input.travel_back {
+        current_era.current_era.previous()
+    } else {
+        None
+    };
+    let Some(destination_era) = destination_era else {
+        return;
+    };
+
+    let (player_transform, player_entity, player_collider, player_layers) = player_query.single();
+    let destination_collidables = grid.collidables_near(
+        &collidable_query,
+        player_transform.translation,
+        player_collider,
+        destination_era,
+    );
+    let previous_era = current_era.current_era;
+    if !editing.0 {
+        if let Some((blocking_entity, blocking_translation)) = check_collision(
+            &player_transform.translation,
+            player_entity,
+            player_collider,
+            player_layers.mask,
+            &destination_collidables,
+        ) {
+            blocked_event_writer.send(TimeTravelBlockedEvent::new(
+                previous_era,
+                destination_era,
+                blocking_entity,
+                blocking_translation,
+            ));
+            return;
+        }
+    }
+
+    current_era.current_era = destination_era;
+    era_event_writer.send(EraChangedEvent::new(previous_era, destination_era));
+}
+
+/// Hides the background layer of the era that was left
+/// and shows the layer of the era that was travelled to.
+fn show_current_era(
+    mut query: Query<&mut Visibility, With<BackgroundParent>>,
     current_era: Res<CurrentEra>,
+    mut event: EventReader<EraChangedEvent>,
 ) {
-    if keyboard.just_released(KeyCode::T) {
-        let mut background_visibility =
-            query.get_component_mut::<Visibility>(current_era.current_era);
-        background_visibility.as_mut().unwrap().is_visible =
-            !background_visibility.as_ref().unwrap().is_visible;
+    for era_changed_event in event.iter() {
+        for (era, is_visible) in [
+            (era_changed_event.previous_era(), false),
+            (era_changed_event.current_era(), true),
+        ] {
+            if let Some(mut visibility) = current_era
+                .root(era)
+                .and_then(|root| query.get_mut(root).ok())
+            {
+                visibility.is_visible = is_visible;
+            }
+        }
+    }
+}
+
+/// Only shows era-scoped entities while the current era is one they exist in.
+fn show_era_members(mut query: Query<(&InEras, &mut Visibility)>, current_era: Res<CurrentEra>) {
+    for (in_eras, mut visibility) in query.iter_mut() {
+        let is_visible = in_eras.contains(current_era.current_era);
+        if visibility.is_visible != is_visible {
+            visibility.is_visible = is_visible;
+        }
+    }
+}
+
+fn report_blocked_time_travel(mut event: EventReader<TimeTravelBlockedEvent>) {
+    for blocked_event in event.iter() {
+        info!(
+            "Travel from {:?} to {:?} blocked by {:?} at {}",
+            blocked_event.current_era(),
+            blocked_event.destination_era(),
+            blocked_event.blocking_entity(),
+            blocked_event.blocking_translation()
+        );
     }
 }
@@ -0,0 +1,221 @@
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use iyes_loopless::prelude::*;
+
+use crate::collisionsmod::components::InteractedWith;
+use crate::events::EraChangedEvent;
+use crate::playermod::rewind::is_rewinding;
+use crate::systemsmod::tick::{GAMEPLAY_STAGE, LATE_TICK_STAGE, TICK};
+
+pub struct CausalityPlugin;
+
+impl Plugin for CausalityPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CausalTimeline>()
+            .add_fixed_timestep_system(
+                TICK,
+                LATE_TICK_STAGE,
+                track_causal_links.label("causallinks"),
+            )
+            .add_fixed_timestep_system(
+                TICK,
+                LATE_TICK_STAGE,
+                record_moved_ancestors
+                    .run_if_not(is_rewinding)
+                    .after("causallinks"),
+            )
+            .add_fixed_timestep_system(
+                TICK,
+                LATE_TICK_STAGE,
+                record_interacted_ancestors
+                    .run_if_not(is_rewinding)
+                    .after("causallinks"),
+            )
+            .add_fixed_timestep_system(
+                TICK,
+                LATE_TICK_STAGE,
+                record_destroyed_ancestors
+                    .run_if_not(is_rewinding)
+                    .after("causallinks"),
+            )
+            .add_fixed_timestep_system(
+                TICK,
+                GAMEPLAY_STAGE,
+                propagate_causal_effects
+                    .run_on_event::<EraChangedEvent>()
+                    .run_if_not(is_rewinding)
+                    .after("timetravel"),
+            )
+            // Also catches ancestors despawned outside of a tick.
+            .add_system_to_stage(CoreStage::PostUpdate, record_destroyed_ancestors);
+    }
+}
+
+/// Declares the entity that this entity becomes in a later era.
+/// Changes to this entity are carried over to its descendant
+/// when the player travels forward in time.
+#[derive(Component)]
+pub struct Descendant(pub Entity);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CausalEffect {
+    Destroyed,
+    Moved(Vec3),
+    Interacted,
+}
+
+/// Tracks which descendant each ancestor is linked to and the effects
+/// waiting to be applied on the next forward trip through time.
+#[derive(Default, Resource)]
+pub struct CausalTimeline {
+    descendants: HashMap<Entity, Entity>,
+    pending_effects: Vec<(Entity, CausalEffect)>,
+}
+
+impl CausalTimeline {
+    /// Queues an effect for a descendant. A move replaces any earlier
+    /// queued move of the same descendant and destruction supersedes
+    /// everything else queued for it.
+    pub fn queue(&mut self, descendant: Entity, effect: CausalEffect) {
+        match effect {
+            CausalEffect::Destroyed => self
+                .pending_effects
+                .retain(|(pending_descendant, _)| *pending_descendant != descendant),
+            CausalEffect::Moved(_) => self.pending_effects.retain(|(pending_descendant, e)| {
+                *pending_descendant != descendant || !matches!(e, CausalEffect::Moved(_))
+            }),
+            CausalEffect::Interacted => {}
+        }
+        if !self.pending_effects.contains(&(descendant, effect)) {
+            self.pending_effects.push((descendant, effect));
+        }
+    }
+}
+
+fn track_causal_links(
+    query: Query<(Entity, &Descendant), Added<Descendant>>,
+    mut timeline: ResMut<CausalTimeline>,
+) {
+    for (ancestor, descendant) in query.iter() {
+        timeline.descendants.insert(ancestor, descendant.0);
+    }
+}
+
+fn record_moved_ancestors(
+    query: Query<(&Descendant, &Transform, ChangeTrackers<Transform>), Changed<Transform>>,
+    mut timeline: ResMut<CausalTimeline>,
+) {
+    for (descendant, transform, transform_tracker) in query.iter() {
+        if !transform_tracker.is_added() {
+            timeline.queue(descendant.0, CausalEffect::Moved(transform.translation));
+        }
+    }
+}
+
+fn record_interacted_ancestors(
+    query: Query<&Descendant, Added<InteractedWith>>,
+    mut timeline: ResMut<CausalTimeline>,
+) {
+    for descendant in query.iter() {
+        timeline.queue(descendant.0, CausalEffect::Interacted);
+    }
+}
+
+/// Runs after commands have been applied so that ancestors
+/// despawned during the tick or frame are seen as destroyed.
+fn record_destroyed_ancestors(
+    removed: RemovedComponents<Descendant>,
+    mut timeline: ResMut<CausalTimeline>,
+) {
+    for ancestor in removed.iter() {
+        if let Some(descendant) = timeline.descendants.remove(&ancestor) {
+            timeline.queue(descendant, CausalEffect::Destroyed);
+        }
+    }
+}
+
+/// Applies the effects recorded in earlier eras to their
+/// descendants whenever the player travels forward in time. Rewinding
+/// back across a trip to the past leaves them pending instead.
+fn propagate_causal_effects(
+    mut commands: Commands,
+    mut transform_query: Query<&mut Transform>,
+    mut event: EventReader<EraChangedEvent>,
+    mut timeline: ResMut<CausalTimeline>,
+) {
+    let mut travelled_forward = false;
+    for era_changed_event in event.iter() {
+        travelled_forward |= !era_changed_event.is_rewind()
+            && era_changed_event.current_era() > era_changed_event.previous_era();
+    }
+    if !travelled_forward {
+        return;
+    }
+    for (descendant, effect) in timeline.pending_effects.drain(..) {
+        match effect {
+            CausalEffect::Destroyed => {
+                if let Some(descendant_entity) = commands.get_entity(descendant) {
+                    descendant_entity.despawn_recursive();
+                }
+            }
+            CausalEffect::Moved(ancestor_translation) => {
+                if let Ok(mut transform) = transform_query.get_mut(descendant) {
+                    transform.translation.x = ancestor_translation.x;
+                    transform.translation.y = ancestor_translation.y;
+                }
+            }
+            CausalEffect::Interacted => {
+                if let Some(mut descendant_entity) = commands.get_entity(descendant) {
+                    descendant_entity.insert(InteractedWith());
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_causal_timeline {
+    use super::*;
+
+    #[test]
+    fn test_queue_moved_replaces_earlier_move() {
+        let mut timeline = CausalTimeline::default();
+        let descendant = Entity::from_raw(1);
+        timeline.queue(descendant, CausalEffect::Moved(Vec3::X));
+        timeline.queue(descendant, CausalEffect::Interacted);
+        timeline.queue(descendant, CausalEffect::Moved(Vec3::Y));
+        assert_eq!(
+            timeline.pending_effects,
+            vec![
+                (descendant, CausalEffect::Interacted),
+                (descendant, CausalEffect::Moved(Vec3::Y))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_queue_destroyed_supersedes_other_effects() {
+        let mut timeline = CausalTimeline::default();
+        let descendant = Entity::from_raw(1);
+        let other_descendant = Entity::from_raw(2);
+        timeline.queue(descendant, CausalEffect::Moved(Vec3::X));
+        timeline.queue(other_descendant, CausalEffect::Interacted);
+        timeline.queue(descendant, CausalEffect::Destroyed);
+        assert_eq!(
+            timeline.pending_effects,
+            vec![
+                (other_descendant, CausalEffect::Interacted),
+                (descendant, CausalEffect::Destroyed)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_queue_ignores_duplicate_interactions() {
+        let mut timeline = CausalTimeline::default();
+        let descendant = Entity::from_raw(1);
+        timeline.queue(descendant, CausalEffect::Interacted);
+        timeline.queue(descendant, CausalEffect::Interacted);
+        assert_eq!(timeline.pending_effects.len(), 1);
+    }
+}
@@ -1,34 +1,100 @@
+use float_ord::FloatOrd;
+
+use crate::events::CollisionEvent;
 use crate::*;
 
 pub struct CollisionsPlugin;
 
 impl Plugin for CollisionsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_startup_system(draw_collidable)
-            .add_system(manage_interaction_events.run_on_event::<InteractionEvent>());
+        app.add_system(manage_interaction_events.run_on_event::<InteractionEvent>())
+            .add_fixed_timestep_system(
+                TICK,
+                COLLISION_STAGE,
+                detect_collisions
+                    .run_if_not(is_rewinding)
+                    .label("collisiondetection")
+                    .after("collisiongrid"),
+            );
+    }
+}
+
+/// Finds every entity overlapping something on a layer in its mask in the
+/// current era, reporting each overlap for as long as it lasts. Swept
+/// entities only report the first thing along their last move.
+fn detect_collisions(
+    query: Query<CollidableQuery>,
+    swept_query: Query<&Swept>,
+    grid: Res<CollisionGrid>,
+    current_era: Res<CurrentEra>,
+    mut collision_writer: EventWriter<CollisionEvent>,
+) {
+    for (transform, entity, collider, layers, in_eras) in query.iter() {
+        if layers.mask == 0 || !current_era.is_present(in_eras) {
+            continue;
+        }
+        let candidate = |other: Entity| {
+            let (other_transform, _, other_collider, other_layers, other_in_eras) =
+                query.get(other).ok()?;
+            (other != entity
+                && other_layers.is_in(layers.mask)
+                && current_era.is_present(other_in_eras))
+            .then_some((other, other_transform.translation, other_collider))
+        };
+
+        if let Ok(swept) = swept_query.get(entity) {
+            let start = swept.previous_translation;
+            let end = transform.translation;
+            let first_hit = grid
+                .nearby_along(start, end, collider)
+                .into_iter()
+                .filter_map(candidate)
+                .filter_map(|(other, other_translation, other_collider)| {
+                    let hit = collider.sweep(start, end, other_collider, other_translation)?;
+                    Some((FloatOrd(hit), other))
+                })
+                .min();
+            if let Some((FloatOrd(hit), other)) = first_hit {
+                collision_writer.send(CollisionEvent::new(entity, other, start.lerp(end, hit)));
+            }
+            continue;
+        }
+
+        for (other, other_translation, other_collider) in grid
+            .nearby(transform.translation, collider)
+            .into_iter()
+            .filter_map(candidate)
+        {
+            if !collider.overlaps(transform.translation, other_collider, other_translation) {
+                continue;
+            }
+            collision_writer.send(CollisionEvent::new(entity, other, transform.translation));
+        }
     }
 }
 
-/// Checks collisions between the player or another
-/// moving entity and all collidable entities.
+/// Checks collisions between the player or another moving
+/// entity and the collidable entities on a layer in its mask,
+/// returning the first collidable hit and its translation.
 pub fn check_collision(
     mover_translation: &Vec3,
-    mover_index: &u32,
-    collidables: &[(Vec3, u32)],
-    mover_size: Vec2,
-) -> Option<Vec3> {
-    let tile = Vec2::new(TILE_SIZE, TILE_SIZE);
-    for (collidable_translation, collidable_index) in collidables.iter() {
-        if mover_index != collidable_index
-            && collide(
+    mover: Entity,
+    mover_collider: &Collider,
+    mover_mask: u32,
+    collidables: &[CollisionCandidate],
+) -> Option<(Entity, Vec3)> {
+    for (collidable_translation, collidable, collidable_collider, collidable_layers) in
+        collidables.iter()
+    {
+        if mover != *collidable
+            && collidable_layers.is_in(mover_mask)
+            && mover_collider.overlaps(
                 *mover_translation,
-                mover_size,
+                collidable_collider,
                 *collidable_translation,
-                tile,
             )
-            .is_some()
         {
-            return Some(*collidable_translation);
+            return Some((*collidable, *collidable_translation));
         };
     }
     None
@@ -40,8 +106,8 @@ fn check_interaction(
     mut pdi_translation: Vec3,
     facing_direction: FacingDirection,
     interaction_translation: Vec3,
+    interaction_collider: &Collider,
 ) -> bool {
-    let tile = Vec2::new(TILE_SIZE, TILE_SIZE);
     let scaled_tile_size = TILE_SIZE * 0.5;
     let interaction_box = match facing_direction {
         FacingDirection::Up => {
@@ -61,28 +127,31 @@ fn check_interaction(
             Vec2::new(scaled_tile_size, scaled_tile_size)
         }
     };
-    collide(
+    Collider::aabb(interaction_box).overlaps(
         pdi_translation,
-        interaction_box,
+        interaction_collider,
         interaction_translation,
-        tile,
     )
-    .is_some()
 }
 
-/// This system checks interaction events between
-/// the player and interactable entities.
+/// This system checks interaction events between the player
+/// and interactable entities present in the current era.
 fn manage_interaction_events(
     mut commands: Commands,
-    query: Query<(&Transform, Entity), With<Interactable>>,
+    query: Query<(&Transform, Entity, &Collider, Option<&InEras>), With<Interactable>>,
     mut event: EventReader<InteractionEvent>,
+    current_era: Res<CurrentEra>,
 ) {
     for interaction_event in event.iter() {
-        for (transform, entity) in query.iter() {
+        for (transform, entity, collider, _) in query
+            .iter()
+            .filter(|(_, _, _, in_eras)| current_era.is_present(*in_eras))
+        {
             let is_interacted = check_interaction(
                 interaction_event.pdi_translation(),
                 interaction_event.facing_direction(),
                 transform.translation,
+                collider,
             );
             if is_interacted {
                 commands.entity(entity).insert(InteractedWith());
@@ -92,37 +161,158 @@ fn manage_interaction_events(
     }
 }
 
-#[derive(Component)]
-struct CollidableTimer(Timer);
-
-fn draw_collidable(mut commands: Commands) {
-    let shape = shapes::Rectangle {
+/// Spawns the entity for a level tile in a single era. Floor tiles
+/// are drawn by the background instead and spawn nothing here.
+pub fn spawn_level_tile(
+    commands: &mut Commands,
+    tile: LevelTile,
+    era: Era,
+    location: Vec2,
+) -> Option<Entity> {
+    let square = shapes::Rectangle {
         extents: Vec2::new(TILE_SIZE, TILE_SIZE),
         origin: RectangleOrigin::Center,
     };
-    let locations = vec![
-        Vec2::new(5.0 * TILE_SIZE, 0.0 * TILE_SIZE),
-        Vec2::new(6.0 * TILE_SIZE, 0.0 * TILE_SIZE),
-        Vec2::new(0.0 * TILE_SIZE, 4.0 * TILE_SIZE),
-    ];
-    for location in locations {
-        commands.spawn((
-            GeometryBuilder::build_as(
-                &shape,
-                DrawMode::Outlined {
-                    fill_mode: FillMode::color(Color::YELLOW_GREEN),
-                    outline_mode: StrokeMode::new(Color::BLACK, TILE_SIZE / 10.0),
-                },
-                Transform::from_translation(Vec3::new(
-                    location.x,
-                    location.y,
-                    PLAYER_LEVEL,
-                )),
+    let circle = |radius| shapes::Circle {
+        radius,
+        center: Vec2::ZERO,
+    };
+    let (fill_color, geometry, collider, layers) = match tile {
+        LevelTile::Floor => return None,
+        LevelTile::Wall => (
+            Color::DARK_GRAY,
+            GeometryBuilder::new().add(&square),
+            Collider::tile(),
+            CollisionLayers::scenery(CollisionLayers::WALL),
+        ),
+        LevelTile::Water => (
+            Color::BLUE,
+            GeometryBuilder::new().add(&square),
+            Collider::tile(),
+            CollisionLayers::scenery(CollisionLayers::WATER),
+        ),
+        LevelTile::Grate => (
+            Color::GRAY,
+            GeometryBuilder::new().add(&square),
+            Collider::tile(),
+            CollisionLayers::scenery(CollisionLayers::GRATE),
+        ),
+        LevelTile::Switch => (
+            Color::GOLD,
+            GeometryBuilder::new().add(&square),
+            Collider::tile(),
+            CollisionLayers::scenery(CollisionLayers::WALL),
+        ),
+        LevelTile::HookshotTarget => (
+            Color::TEAL,
+            GeometryBuilder::new().add(&square),
+            Collider::tile(),
+            CollisionLayers::scenery(CollisionLayers::WALL | CollisionLayers::HOOKSHOT_TARGET),
+        ),
+        LevelTile::Block => (
+            Color::YELLOW_GREEN,
+            GeometryBuilder::new().add(&square),
+            Collider::tile(),
+            CollisionLayers::scenery(CollisionLayers::WALL | CollisionLayers::HOOKSHOT_TARGET),
+        ),
+        LevelTile::Sapling => (
+            Color::LIME_GREEN,
+            GeometryBuilder::new().add(&circle(TILE_SIZE / 4.0)),
+            Collider::circle(TILE_SIZE / 4.0),
+            CollisionLayers::scenery(CollisionLayers::WALL),
+        ),
+        LevelTile::Tree => (
+            Color::DARK_GREEN,
+            GeometryBuilder::new().add(&circle(TILE_SIZE / 2.0)),
+            Collider::circle(TILE_SIZE / 2.0),
+            CollisionLayers::scenery(CollisionLayers::WALL),
+        ),
+        LevelTile::Pot => (
+            Color::ORANGE_RED,
+            GeometryBuilder::new().add(&circle(TILE_SIZE * 0.4)),
+            Collider::circle(TILE_SIZE * 0.4),
+            CollisionLayers::scenery(CollisionLayers::WALL | CollisionLayers::HOOKSHOT_TARGET),
+        ),
+    };
+    let mut entity = commands.spawn((
+        geometry.build(
+            DrawMode::Outlined {
+                fill_mode: FillMode::color(fill_color),
+                outline_mode: StrokeMode::new(Color::BLACK, TILE_SIZE / 10.0),
+            },
+            Transform::from_translation(location.extend(PLAYER_LEVEL)),
+        ),
+        collider,
+        layers,
+        InEras(vec![era]),
+        LevelEntity,
+    ));
+    match tile {
+        LevelTile::Switch => {
+            entity.insert(Interactable());
+        }
+        LevelTile::Block => {
+            entity.insert(Interactable());
+        }
+        LevelTile::Sapling => {
+            entity.insert(Destructible());
+        }
+        LevelTile::Pot => {
+            entity.insert(Weight::Light);
+        }
+        _ => {}
+    }
+    Some(entity.id())
+}
+
+#[cfg(test)]
+mod test_check_collision {
+    use super::*;
+
+    fn wall(entity: Entity, translation: Vec3) -> CollisionCandidate {
+        (
+            translation,
+            entity,
+            Collider::tile(),
+            CollisionLayers::scenery(CollisionLayers::WALL),
+        )
+    }
+
+    #[test]
+    fn test_check_collision_returns_hit_entity() {
+        let mover = Entity::from_raw(1);
+        let near = Entity::from_raw(2);
+        let collidables = [
+            wall(Entity::from_raw(3), Vec3::new(3.0 * TILE_SIZE, 0.0, 0.0)),
+            wall(near, Vec3::new(0.5 * TILE_SIZE, 0.0, 0.0)),
+        ];
+        assert_eq!(
+            check_collision(
+                &Vec3::ZERO,
+                mover,
+                &Collider::tile(),
+                CollisionLayers::player().mask,
+                &collidables
+            ),
+            Some((near, Vec3::new(0.5 * TILE_SIZE, 0.0, 0.0)))
+        );
+    }
+
+    #[test]
+    fn test_check_collision_tells_recycled_entities_apart() {
+        let mover = Entity::from_raw(1);
+        let recycled = Entity::from_bits(1 << 32 | 1);
+        assert_eq!(mover.index(), recycled.index());
+        let collidables = [wall(mover, Vec3::ZERO), wall(recycled, Vec3::ZERO)];
+        assert_eq!(
+            check_collision(
+                &Vec3::ZERO,
+                mover,
+                &Collider::tile(),
+                CollisionLayers::player().mask,
+                &collidables
             ),
-            Collidable(),
-            Interactable(),
-            Hookshotable(),
-            CollidableTimer(Timer::from_seconds(1.0, TimerMode::Once)),
-        ));
+            Some((recycled, Vec3::ZERO))
+        );
     }
 }
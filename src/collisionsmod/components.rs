@@ -1,7 +1,184 @@
 use bevy::prelude::*;
 
-#[derive(Component)]
-pub struct Collidable();
+use crate::resources::Era;
+use crate::TILE_SIZE;
+
+/// What an entity is (`memberships`) and what stops it (`mask`),
+/// as bit flags made from the layer constants.
+#[derive(Clone, Copy, Component, Debug, Eq, PartialEq)]
+pub struct CollisionLayers {
+    pub memberships: u32,
+    pub mask: u32,
+}
+
+impl CollisionLayers {
+    pub const PLAYER: u32 = 1 << 0;
+    pub const ENEMY: u32 = 1 << 1;
+    pub const PROJECTILE: u32 = 1 << 2;
+    pub const WALL: u32 = 1 << 3;
+    pub const WATER: u32 = 1 << 4;
+    pub const GRATE: u32 = 1 << 5;
+    pub const HOOKSHOT_TARGET: u32 = 1 << 6;
+
+    pub fn new(memberships: u32, mask: u32) -> Self {
+        Self { memberships, mask }
+    }
+
+    /// Level geometry, which never moves into anything itself.
+    pub fn scenery(memberships: u32) -> Self {
+        Self::new(memberships, 0)
+    }
+
+    pub fn player() -> Self {
+        Self::new(
+            Self::PLAYER,
+            Self::WALL | Self::WATER | Self::GRATE | Self::ENEMY,
+        )
+    }
+
+    /// Arrows fly over water but not through grates.
+    pub fn arrow() -> Self {
+        Self::new(Self::PROJECTILE, Self::WALL | Self::GRATE | Self::ENEMY)
+    }
+
+    /// The hookshot latches onto targets and bounces off walls,
+    /// passing over water and through grates.
+    pub fn hookshot() -> Self {
+        Self::new(Self::PROJECTILE, Self::WALL | Self::HOOKSHOT_TARGET)
+    }
+
+    /// Whether the entity is on any of the layers in `mask`.
+    pub fn is_in(&self, mask: u32) -> bool {
+        self.memberships & mask != 0
+    }
+}
+
+/// The shape an entity collides with, centred on its
+/// translation plus `offset`.
+#[derive(Clone, Copy, Component, Debug, PartialEq)]
+pub enum Collider {
+    Aabb { half_extents: Vec2, offset: Vec2 },
+    Circle { radius: f32, offset: Vec2 },
+}
+
+impl Collider {
+    pub fn aabb(size: Vec2) -> Self {
+        Collider::Aabb {
+            half_extents: size / 2.0,
+            offset: Vec2::ZERO,
+        }
+    }
+
+    pub fn circle(radius: f32) -> Self {
+        Collider::Circle {
+            radius,
+            offset: Vec2::ZERO,
+        }
+    }
+
+    /// A square filling a whole tile.
+    pub fn tile() -> Self {
+        Collider::aabb(Vec2::new(TILE_SIZE, TILE_SIZE))
+    }
+
+    fn centre(&self, translation: Vec3) -> Vec2 {
+        let offset = match self {
+            Collider::Aabb { offset, .. } | Collider::Circle { offset, .. } => *offset,
+        };
+        translation.truncate() + offset
+    }
+
+    /// Half extents of the smallest box around the collider.
+    pub fn half_extents(&self) -> Vec2 {
+        match self {
+            Collider::Aabb { half_extents, .. } => *half_extents,
+            Collider::Circle { radius, .. } => Vec2::splat(*radius),
+        }
+    }
+
+    /// Smallest and largest corners of the box around the collider.
+    pub fn bounds(&self, translation: Vec3) -> (Vec2, Vec2) {
+        let centre = self.centre(translation);
+        (centre - self.half_extents(), centre + self.half_extents())
+    }
+
+    /// Whether two colliders overlap. Shapes that only touch do not.
+    pub fn overlaps(&self, translation: Vec3, other: &Collider, other_translation: Vec3) -> bool {
+        let centre = self.centre(translation);
+        let other_centre = other.centre(other_translation);
+        match (self, other) {
+            (
+                Collider::Aabb { half_extents, .. },
+                Collider::Aabb {
+                    half_extents: other_half_extents,
+                    ..
+                },
+            ) => {
+                let distance = (centre - other_centre).abs();
+                let reach = *half_extents + *other_half_extents;
+                distance.x < reach.x && distance.y < reach.y
+            }
+            (
+                Collider::Circle { radius, .. },
+                Collider::Circle {
+                    radius: other_radius,
+                    ..
+                },
+            ) => centre.distance_squared(other_centre) < (radius + other_radius).powi(2),
+            (Collider::Aabb { half_extents, .. }, Collider::Circle { radius, .. }) => {
+                circle_overlaps_aabb(other_centre, *radius, centre, *half_extents)
+            }
+            (Collider::Circle { radius, .. }, Collider::Aabb { half_extents, .. }) => {
+                circle_overlaps_aabb(centre, *radius, other_centre, *half_extents)
+            }
+        }
+    }
+
+    /// How far along the move from `start` to `end` the box around this
+    /// collider first overlaps the box around `other`, from 0.0 at the start
+    /// to 1.0 at the end. Moves that only graze the other box never hit it.
+    pub fn sweep(
+        &self,
+        start: Vec3,
+        end: Vec3,
+        other: &Collider,
+        other_translation: Vec3,
+    ) -> Option<f32> {
+        let origin = self.centre(start);
+        let direction = self.centre(end) - origin;
+        let other_centre = other.centre(other_translation);
+        let reach = self.half_extents() + other.half_extents();
+        let mut entry = f32::NEG_INFINITY;
+        let mut exit = f32::INFINITY;
+        for axis in 0..2 {
+            if direction[axis] == 0.0 {
+                if (origin[axis] - other_centre[axis]).abs() >= reach[axis] {
+                    return None;
+                }
+                continue;
+            }
+            let near = (other_centre[axis] - reach[axis] - origin[axis]) / direction[axis];
+            let far = (other_centre[axis] + reach[axis] - origin[axis]) / direction[axis];
+            entry = entry.max(near.min(far));
+            exit = exit.min(near.max(far));
+        }
+        if entry < exit && entry <= 1.0 && exit > 0.0 {
+            Some(entry.max(0.0))
+        } else {
+            None
+        }
+    }
+}
+
+fn circle_overlaps_aabb(
+    circle_centre: Vec2,
+    radius: f32,
+    aabb_centre: Vec2,
+    half_extents: Vec2,
+) -> bool {
+    let closest = circle_centre.clamp(aabb_centre - half_extents, aabb_centre + half_extents);
+    circle_centre.distance_squared(closest) < radius * radius
+}
 
 #[derive(Component)]
 pub struct Interactable();
@@ -9,3 +186,171 @@ pub struct Interactable();
 #[derive(Component)]
 pub struct InteractedWith();
 
+/// Marks an entity that is destroyed when struck by an arrow.
+#[derive(Component)]
+pub struct Destructible();
+
+/// Marks a fast-moving entity whose collisions are checked along the
+/// whole of its last move, so it cannot skip through thin collidables.
+#[derive(Clone, Component, Copy)]
+pub struct Swept {
+    pub previous_translation: Vec3,
+}
+
+impl Swept {
+    pub fn new(translation: Vec3) -> Self {
+        Self {
+            previous_translation: translation,
+        }
+    }
+}
+
+/// How a latched hookshot moves a target. Targets without
+/// a weight are heavy.
+#[derive(Clone, Component, Copy, Debug, PartialEq)]
+pub enum Weight {
+    /// Pulls the player over to the target.
+    Heavy,
+    /// Drags the target back to the player.
+    Light,
+}
+
+/// Restricts an entity to the listed eras. Entities
+/// without this component exist in every era.
+#[derive(Component)]
+pub struct InEras(pub Vec<Era>);
+
+impl InEras {
+    pub fn contains(&self, era: Era) -> bool {
+        self.0.contains(&era)
+    }
+}
+
+/// Whether an entity with the given era membership exists in `era`.
+pub fn exists_in_era(in_eras: Option<&InEras>, era: Era) -> bool {
+    in_eras.is_none_or(|in_eras| in_eras.contains(era))
+}
+
+#[cfg(test)]
+mod test_collision_layers {
+    use super::*;
+
+    #[test]
+    fn test_water_stops_player_but_not_arrows() {
+        let water = CollisionLayers::scenery(CollisionLayers::WATER);
+        assert!(water.is_in(CollisionLayers::player().mask));
+        assert!(!water.is_in(CollisionLayers::arrow().mask));
+    }
+
+    #[test]
+    fn test_hookshot_passes_through_grates() {
+        let grate = CollisionLayers::scenery(CollisionLayers::GRATE);
+        let target =
+            CollisionLayers::scenery(CollisionLayers::WALL | CollisionLayers::HOOKSHOT_TARGET);
+        assert!(!grate.is_in(CollisionLayers::hookshot().mask));
+        assert!(target.is_in(CollisionLayers::hookshot().mask));
+        assert!(
+            CollisionLayers::scenery(CollisionLayers::WALL).is_in(CollisionLayers::hookshot().mask)
+        );
+        assert!(!CollisionLayers::player().is_in(grate.mask));
+    }
+}
+
+#[cfg(test)]
+mod test_collider {
+    use super::*;
+
+    #[test]
+    fn test_aabbs_overlap_but_not_when_touching() {
+        let square = Collider::tile();
+        let post = Collider::aabb(Vec2::new(TILE_SIZE / 2.0, TILE_SIZE / 2.0));
+        assert!(square.overlaps(Vec3::ZERO, &post, Vec3::new(0.7 * TILE_SIZE, 0.0, 0.0)));
+        assert!(!square.overlaps(Vec3::ZERO, &post, Vec3::new(0.75 * TILE_SIZE, 0.0, 0.0)));
+    }
+
+    #[test]
+    fn test_aabb_offset_moves_the_shape() {
+        let gate = Collider::Aabb {
+            half_extents: Vec2::new(TILE_SIZE, TILE_SIZE / 2.0),
+            offset: Vec2::new(TILE_SIZE / 2.0, 0.0),
+        };
+        let square = Collider::tile();
+        assert!(gate.overlaps(Vec3::ZERO, &square, Vec3::new(1.9 * TILE_SIZE, 0.0, 0.0)));
+        assert!(!gate.overlaps(Vec3::ZERO, &square, Vec3::new(-1.1 * TILE_SIZE, 0.0, 0.0)));
+    }
+
+    #[test]
+    fn test_circles_overlap() {
+        let pillar = Collider::circle(TILE_SIZE / 2.0);
+        assert!(pillar.overlaps(Vec3::ZERO, &pillar, Vec3::new(0.9 * TILE_SIZE, 0.0, 0.0)));
+        assert!(!pillar.overlaps(Vec3::ZERO, &pillar, Vec3::new(TILE_SIZE, 0.0, 0.0)));
+    }
+
+    #[test]
+    fn test_circle_misses_aabb_corner() {
+        let pillar = Collider::circle(TILE_SIZE / 2.0);
+        let square = Collider::tile();
+        let diagonal = Vec3::new(0.9 * TILE_SIZE, 0.9 * TILE_SIZE, 0.0);
+        assert!(!pillar.overlaps(Vec3::ZERO, &square, diagonal));
+        assert!(square.overlaps(diagonal, &square, Vec3::ZERO));
+        assert!(square.overlaps(Vec3::ZERO, &pillar, Vec3::new(0.9 * TILE_SIZE, 0.0, 0.0)));
+    }
+
+    #[test]
+    fn test_sweep_hits_collidable_skipped_over() {
+        let arrow = Collider::aabb(Vec2::new(TILE_SIZE / 10.0, TILE_SIZE / 2.0));
+        let sapling = Collider::circle(TILE_SIZE / 4.0);
+        let start = Vec3::new(0.0, -TILE_SIZE, 0.0);
+        let end = Vec3::new(0.0, TILE_SIZE, 0.0);
+        assert!(!arrow.overlaps(end, &sapling, Vec3::ZERO));
+        assert_eq!(arrow.sweep(start, end, &sapling, Vec3::ZERO), Some(0.25));
+    }
+
+    #[test]
+    fn test_sweep_misses_what_it_passes_beside() {
+        let arrow = Collider::aabb(Vec2::new(TILE_SIZE / 10.0, TILE_SIZE / 2.0));
+        let start = Vec3::new(TILE_SIZE, -TILE_SIZE, 0.0);
+        let end = Vec3::new(TILE_SIZE, TILE_SIZE, 0.0);
+        assert_eq!(arrow.sweep(start, end, &Collider::tile(), Vec3::ZERO), None);
+        let short_end = Vec3::new(0.0, -0.8 * TILE_SIZE, 0.0);
+        assert_eq!(
+            arrow.sweep(
+                Vec3::new(0.0, -TILE_SIZE, 0.0),
+                short_end,
+                &Collider::tile(),
+                Vec3::ZERO
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_sweep_starting_inside_hits_immediately() {
+        let square = Collider::tile();
+        assert_eq!(
+            square.sweep(
+                Vec3::ZERO,
+                Vec3::new(TILE_SIZE, 0.0, 0.0),
+                &square,
+                Vec3::ZERO
+            ),
+            Some(0.0)
+        );
+        assert_eq!(
+            square.sweep(Vec3::ZERO, Vec3::ZERO, &square, Vec3::ZERO),
+            Some(0.0)
+        );
+    }
+
+    #[test]
+    fn test_bounds_include_offset() {
+        let collider = Collider::Circle {
+            radius: 10.0,
+            offset: Vec2::new(5.0, 0.0),
+        };
+        assert_eq!(
+            collider.bounds(Vec3::new(1.0, 2.0, 3.0)),
+            (Vec2::new(-4.0, -8.0), Vec2::new(16.0, 12.0))
+        );
+    }
+}
@@ -0,0 +1,220 @@
+use bevy::ecs::query::ReadOnlyWorldQuery;
+use bevy::utils::HashMap;
+
+use crate::levelmod::level::tile_position;
+use crate::*;
+
+pub struct CollisionGridPlugin;
+
+impl Plugin for CollisionGridPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CollisionGrid>()
+            .add_fixed_timestep_system(
+                TICK,
+                COLLISION_STAGE,
+                update_collision_grid.label("collisiongrid"),
+            )
+            .add_system_to_stage(CoreStage::PostUpdate, update_collision_grid);
+    }
+}
+
+pub type CollidableQuery<'a> = (
+    &'a Transform,
+    Entity,
+    &'a Collider,
+    &'a CollisionLayers,
+    Option<&'a InEras>,
+);
+
+/// A collidable as passed to `check_collision`.
+pub type CollisionCandidate = (Vec3, Entity, Collider, CollisionLayers);
+
+/// Collidables bucketed by every tile their collider covers, so
+/// that movers only need to check the cells they cover themselves.
+#[derive(Default, Resource)]
+pub struct CollisionGrid {
+    cells: HashMap<(i32, i32), Vec<Entity>>,
+    entity_cells: HashMap<Entity, Vec<(i32, i32)>>,
+}
+
+/// The tiles covered by the box around a collider.
+fn covered_cells(translation: Vec3, collider: &Collider) -> impl Iterator<Item = (i32, i32)> {
+    let (min, max) = collider.bounds(translation);
+    cells_between(min, max)
+}
+
+/// The tiles covered by the box from `min` to `max`.
+fn cells_between(min: Vec2, max: Vec2) -> impl Iterator<Item = (i32, i32)> {
+    let (min_x, min_y) = tile_position(min.extend(0.0));
+    let (max_x, max_y) = tile_position(max.extend(0.0));
+    (min_x..=max_x).flat_map(move |x| (min_y..=max_y).map(move |y| (x, y)))
+}
+
+impl CollisionGrid {
+    pub fn insert(&mut self, entity: Entity, translation: Vec3, collider: &Collider) {
+        let cells: Vec<(i32, i32)> = covered_cells(translation, collider).collect();
+        if self.entity_cells.get(&entity) == Some(&cells) {
+            return;
+        }
+        self.remove(entity);
+        for cell in cells.iter() {
+            self.cells.entry(*cell).or_default().push(entity);
+        }
+        self.entity_cells.insert(entity, cells);
+    }
+
+    pub fn remove(&mut self, entity: Entity) {
+        let Some(cells) = self.entity_cells.remove(&entity) else {
+            return;
+        };
+        for cell in cells {
+            if let Some(entities) = self.cells.get_mut(&cell) {
+                entities.retain(|cell_entity| *cell_entity != entity);
+                if entities.is_empty() {
+                    self.cells.remove(&cell);
+                }
+            }
+        }
+    }
+
+    /// Entities sharing a cell with `collider` placed at `translation`,
+    /// each listed once.
+    pub fn nearby(&self, translation: Vec3, collider: &Collider) -> Vec<Entity> {
+        self.entities_in(covered_cells(translation, collider))
+    }
+
+    /// Entities sharing a cell with `collider` anywhere along
+    /// its move from `start` to `end`, each listed once.
+    pub fn nearby_along(&self, start: Vec3, end: Vec3, collider: &Collider) -> Vec<Entity> {
+        let (start_min, start_max) = collider.bounds(start);
+        let (end_min, end_max) = collider.bounds(end);
+        self.entities_in(cells_between(
+            start_min.min(end_min),
+            start_max.max(end_max),
+        ))
+    }
+
+    fn entities_in(&self, cells: impl Iterator<Item = (i32, i32)>) -> Vec<Entity> {
+        let mut nearby: Vec<Entity> = cells
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .copied()
+            .collect();
+        nearby.sort_unstable();
+        nearby.dedup();
+        nearby
+    }
+
+    /// The nearby entities matched by `query` that exist in `era`.
+    pub fn collidables_near<F: ReadOnlyWorldQuery>(
+        &self,
+        query: &Query<CollidableQuery, F>,
+        translation: Vec3,
+        collider: &Collider,
+        era: Era,
+    ) -> Vec<CollisionCandidate> {
+        self.nearby(translation, collider)
+            .into_iter()
+            .filter_map(|entity| query.get(entity).ok())
+            .filter(|(_, _, _, _, in_eras)| exists_in_era(*in_eras, era))
+            .map(|(transform, entity, collider, layers, _)| {
+                (transform.translation, entity, *collider, *layers)
+            })
+            .collect()
+    }
+}
+
+/// Runs before collisions are detected, and again after commands have been
+/// applied so that collidables despawned by collision handlers are seen.
+fn update_collision_grid(
+    query: Query<
+        (Entity, &Transform, &Collider),
+        (
+            With<CollisionLayers>,
+            Or<(
+                Added<CollisionLayers>,
+                Changed<Transform>,
+                Changed<Collider>,
+            )>,
+        ),
+    >,
+    removed: RemovedComponents<CollisionLayers>,
+    mut grid: ResMut<CollisionGrid>,
+) {
+    for entity in removed.iter() {
+        grid.remove(entity);
+    }
+    for (entity, transform, collider) in query.iter() {
+        grid.insert(entity, transform.translation, collider);
+    }
+}
+
+#[cfg(test)]
+mod test_collision_grid {
+    use super::*;
+
+    fn tile(x: f32, y: f32) -> Vec3 {
+        Vec3::new(x * TILE_SIZE, y * TILE_SIZE, 0.0)
+    }
+
+    #[test]
+    fn test_nearby_only_returns_neighbouring_cells() {
+        let mut grid = CollisionGrid::default();
+        let near = Entity::from_raw(1);
+        let far = Entity::from_raw(2);
+        grid.insert(near, tile(1.0, 0.3), &Collider::tile());
+        grid.insert(far, tile(5.0, 0.0), &Collider::tile());
+        assert_eq!(grid.nearby(tile(0.0, 0.0), &Collider::tile()), vec![near]);
+    }
+
+    #[test]
+    fn test_wide_collider_is_found_from_every_cell_it_covers() {
+        let mut grid = CollisionGrid::default();
+        let gate = Entity::from_raw(1);
+        grid.insert(
+            gate,
+            tile(0.0, 0.0),
+            &Collider::aabb(tile(3.0, 1.0).truncate()),
+        );
+        let post = Collider::circle(TILE_SIZE / 4.0);
+        assert_eq!(grid.nearby(tile(1.0, 0.0), &post), vec![gate]);
+        assert_eq!(grid.nearby(tile(-1.0, 0.0), &post), vec![gate]);
+        assert!(grid.nearby(tile(3.0, 0.0), &post).is_empty());
+    }
+
+    #[test]
+    fn test_nearby_along_covers_the_whole_move() {
+        let mut grid = CollisionGrid::default();
+        let skipped = Entity::from_raw(1);
+        let beside = Entity::from_raw(2);
+        grid.insert(skipped, tile(0.0, 2.0), &Collider::tile());
+        grid.insert(beside, tile(2.0, 2.0), &Collider::tile());
+        let arrow = Collider::aabb(Vec2::new(TILE_SIZE / 10.0, TILE_SIZE / 2.0));
+        assert!(grid.nearby(tile(0.0, 4.0), &arrow).is_empty());
+        assert_eq!(
+            grid.nearby_along(tile(0.0, 0.0), tile(0.0, 4.0), &arrow),
+            vec![skipped]
+        );
+    }
+
+    #[test]
+    fn test_insert_moves_entity_between_cells() {
+        let mut grid = CollisionGrid::default();
+        let entity = Entity::from_raw(1);
+        let post = Collider::circle(TILE_SIZE / 4.0);
+        grid.insert(entity, tile(0.0, 0.0), &post);
+        grid.insert(entity, tile(4.0, 0.0), &post);
+        assert!(grid.nearby(tile(0.0, 0.0), &post).is_empty());
+        assert_eq!(grid.nearby(tile(4.0, 0.0), &post).len(), 1);
+    }
+
+    #[test]
+    fn test_remove_clears_entity() {
+        let mut grid = CollisionGrid::default();
+        let entity = Entity::from_raw(1);
+        grid.insert(entity, tile(0.0, 0.0), &Collider::tile());
+        grid.remove(entity);
+        assert!(grid.cells.is_empty());
+        assert!(grid.entity_cells.is_empty());
+    }
+}
@@ -3,11 +3,14 @@ use bevy::app::PluginGroupBuilder;
 
 pub mod collisions;
 pub mod components;
+pub mod grid;
 
 pub struct CollisionsModPluginGroup;
 
 impl PluginGroup for CollisionsModPluginGroup {
     fn build(self) -> PluginGroupBuilder {
-        PluginGroupBuilder::start::<Self>().add(CollisionsPlugin)
+        PluginGroupBuilder::start::<Self>()
+            .add(CollisionsPlugin)
+            .add(CollisionGridPlugin)
     }
 }
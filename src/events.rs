@@ -1,6 +1,7 @@
 use bevy::prelude::*;
 
 use crate::playermod::player::FacingDirection;
+use crate::resources::Era;
 
 pub struct InteractionEvent {
     pdi_translation: Vec3,
@@ -24,10 +25,122 @@ impl InteractionEvent {
     }
 }
 
+pub struct EraChangedEvent {
+    previous_era: Era,
+    current_era: Era,
+    is_rewind: bool,
+}
+
+impl EraChangedEvent {
+    pub fn new(previous_era: Era, current_era: Era) -> Self {
+        Self {
+            previous_era,
+            current_era,
+            is_rewind: false,
+        }
+    }
+
+    /// An era change made by rewinding, which undoes
+    /// a trip through time rather than making one.
+    pub fn rewound(previous_era: Era, current_era: Era) -> Self {
+        Self {
+            previous_era,
+            current_era,
+            is_rewind: true,
+        }
+    }
+
+    pub fn previous_era(&self) -> Era {
+        self.previous_era
+    }
+
+    pub fn current_era(&self) -> Era {
+        self.current_era
+    }
+
+    pub fn is_rewind(&self) -> bool {
+        self.is_rewind
+    }
+}
+
+/// Sent when the player tries to travel to an era
+/// where a collidable occupies their landing spot.
+pub struct TimeTravelBlockedEvent {
+    current_era: Era,
+    destination_era: Era,
+    blocking_entity: Entity,
+    blocking_translation: Vec3,
+}
+
+impl TimeTravelBlockedEvent {
+    pub fn new(
+        current_era: Era,
+        destination_era: Era,
+        blocking_entity: Entity,
+        blocking_translation: Vec3,
+    ) -> Self {
+        Self {
+            current_era,
+            destination_era,
+            blocking_entity,
+            blocking_translation,
+        }
+    }
+
+    pub fn current_era(&self) -> Era {
+        self.current_era
+    }
+
+    pub fn destination_era(&self) -> Era {
+        self.destination_era
+    }
+
+    pub fn blocking_entity(&self) -> Entity {
+        self.blocking_entity
+    }
+
+    pub fn blocking_translation(&self) -> Vec3 {
+        self.blocking_translation
+    }
+}
+
+/// Sent when a moving entity overlaps an entity on a layer in its mask.
+pub struct CollisionEvent {
+    entity: Entity,
+    other: Entity,
+    contact: Vec3,
+}
+
+impl CollisionEvent {
+    pub fn new(entity: Entity, other: Entity, contact: Vec3) -> Self {
+        Self {
+            entity,
+            other,
+            contact,
+        }
+    }
+
+    pub fn entity(&self) -> Entity {
+        self.entity
+    }
+
+    pub fn other(&self) -> Entity {
+        self.other
+    }
+
+    /// Where the moving entity was when it first touched the other.
+    pub fn contact(&self) -> Vec3 {
+        self.contact
+    }
+}
+
 pub struct EventPlugin;
 
 impl Plugin for EventPlugin {
     fn build(&self, app: &mut App) {
-        app.add_event::<InteractionEvent>();
+        app.add_event::<InteractionEvent>()
+            .add_event::<EraChangedEvent>()
+            .add_event::<TimeTravelBlockedEvent>()
+            .add_event::<CollisionEvent>();
     }
 }
@@ -0,0 +1,502 @@
+use bevy::input::gamepad::{GamepadEventRaw, GamepadEventType, GamepadInfo};
+use bevy::input::keyboard::KeyboardInput;
+use bevy::input::{ButtonState, InputPlugin};
+use bevy::time::TimeUpdateStrategy;
+use bevy::utils::Instant;
+use bevy::window::CursorMoved;
+
+use crate::levelmod::level::Level;
+use crate::*;
+
+/// Path the harness level would be saved to, were the editor to save it.
+const HARNESS_LEVEL: &str = "levels/harness.level";
+
+/// The game built without a window, renderer or shape drawing. Time
+/// only passes when the harness advances it, one tick per update, so
+/// the same presses always play out the same way.
+pub struct Harness {
+    pub app: App,
+    now: Instant,
+}
+
+impl Harness {
+    /// Builds the game on a level given as `.level` source, and runs
+    /// it until the level has spawned without advancing any ticks.
+    pub fn new(level_source: &str) -> Self {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugin(TransformPlugin)
+            .add_plugin(HierarchyPlugin)
+            .add_plugin(InputPlugin)
+            .add_plugin(AssetPlugin::default())
+            .add_event::<CursorMoved>();
+        add_game_plugins(&mut app);
+
+        let level = Level::parse(level_source).expect("harness level should parse");
+        let handle = app.world.resource_mut::<Assets<Level>>().add(level);
+        app.insert_resource(CurrentLevel {
+            handle,
+            path: HARNESS_LEVEL,
+        })
+        .init_resource::<KeyBindings>();
+        let now = app.world.resource::<Time>().startup();
+        app.insert_resource(TimeUpdateStrategy::ManualInstant(now));
+
+        let mut harness = Self { app, now };
+        // Startup sends the level's asset event, and the next update spawns it.
+        harness.app.update();
+        harness.app.update();
+        harness
+    }
+
+    /// Holds a key down from the next update until it is released.
+    pub fn press(&mut self, key: KeyCode) {
+        self.send_key(key, ButtonState::Pressed);
+    }
+
+    pub fn release(&mut self, key: KeyCode) {
+        self.send_key(key, ButtonState::Released);
+    }
+
+    /// Presses a key for a single tick.
+    pub fn tap(&mut self, key: KeyCode) {
+        self.press(key);
+        self.advance(1);
+        self.release(key);
+    }
+
+    /// Plugs in the harness gamepad, ready to take effect on the next update.
+    pub fn connect_gamepad(&mut self) {
+        self.send_gamepad(GamepadEventType::Connected(GamepadInfo {
+            name: "harness".to_string(),
+        }));
+    }
+
+    /// Moves a button or stick of the harness gamepad.
+    pub fn send_gamepad(&mut self, event_type: GamepadEventType) {
+        self.app
+            .world
+            .send_event(GamepadEventRaw::new(Gamepad::new(0), event_type));
+    }
+
+    pub fn advance(&mut self, ticks: u32) {
+        for _ in 0..ticks {
+            self.now += TICK_DURATION;
+            self.app
+                .insert_resource(TimeUpdateStrategy::ManualInstant(self.now));
+            self.app.update();
+        }
+    }
+
+    pub fn player_translation(&mut self) -> Vec3 {
+        self.app
+            .world
+            .query_filtered::<&Transform, With<Player>>()
+            .single(&self.app.world)
+            .translation
+    }
+
+    pub fn count<C: Component>(&mut self) -> usize {
+        self.app
+            .world
+            .query_filtered::<(), With<C>>()
+            .iter(&self.app.world)
+            .count()
+    }
+
+    fn send_key(&mut self, key: KeyCode, state: ButtonState) {
+        self.app.world.send_event(KeyboardInput {
+            scan_code: 0,
+            key_code: Some(key),
+            state,
+        });
+    }
+}
+
+#[cfg(test)]
+mod test_harness {
+    use super::*;
+
+    const OPEN_LEVEL: &str = "[present]\n.....\n.....\n..P..\n.....\n.....\n";
+
+    #[test]
+    fn test_held_key_turns_then_walks_onto_a_tile() {
+        let mut harness = Harness::new(OPEN_LEVEL);
+        harness.press(KeyCode::D);
+        harness.advance(20);
+        harness.release(KeyCode::D);
+        let walked = harness.player_translation();
+        assert!(walked.x > 0.0 && walked.x < TILE_SIZE);
+        assert_eq!(walked.y, 0.0);
+
+        harness.advance(60);
+        assert_eq!(harness.player_translation().x, TILE_SIZE);
+    }
+
+    #[test]
+    fn test_wall_stops_the_player() {
+        let mut harness = Harness::new("[present]\n.P#\n");
+        harness.press(KeyCode::D);
+        harness.advance(60);
+        assert_eq!(harness.player_translation().x, 0.0);
+    }
+
+    #[test]
+    fn test_arrow_breaks_on_a_sapling() {
+        let mut harness = Harness::new("[present]\n.s.\n...\n.P.\n");
+        assert_eq!(harness.count::<Destructible>(), 1);
+        harness.tap(KeyCode::J);
+        assert_eq!(harness.count::<Arrow>(), 1);
+
+        harness.advance(30);
+        assert_eq!(harness.count::<Arrow>(), 0);
+        assert_eq!(harness.count::<Destructible>(), 0);
+    }
+
+    #[test]
+    fn test_hookshot_pulls_the_player_to_a_target() {
+        let mut harness = Harness::new("[present]\nH\n.\n~\nP\n");
+        harness.tap(KeyCode::H);
+        assert!(harness.app.world.resource::<HookshotFiring>().0);
+        harness.advance(16);
+        assert_eq!(harness.count::<HookshotHitBlock>(), 1);
+        harness.advance(30);
+        assert_eq!(harness.player_translation().y, 2.0 * TILE_SIZE);
+        assert!(!harness.app.world.resource::<HookshotFiring>().0);
+        assert_eq!(harness.count::<Hookshot>(), 0);
+    }
+
+    #[test]
+    fn test_hookshot_comes_back_from_a_target_behind_water() {
+        let mut harness = Harness::new("[present]\nH\n~\n.\nP\n");
+        harness.tap(KeyCode::H);
+        harness.advance(60);
+        assert_eq!(harness.count::<Hookshot>(), 0);
+        assert_eq!(harness.player_translation().y, 0.0);
+    }
+
+    #[test]
+    fn test_no_time_travel_while_the_hookshot_pulls() {
+        let mut harness = Harness::new("[present]\nH\n.\n.\nP\n[future]\n.\n#\n.\n.\n");
+        harness.tap(KeyCode::H);
+        harness.advance(16);
+        assert_eq!(harness.count::<HookshotHitBlock>(), 1);
+        harness.tap(KeyCode::T);
+        harness.advance(30);
+        assert_eq!(
+            harness.app.world.resource::<CurrentEra>().current_era,
+            Era::Present
+        );
+        assert_eq!(harness.player_translation().y, 2.0 * TILE_SIZE);
+    }
+
+    #[test]
+    fn test_hookshot_drags_a_pot_to_the_player() {
+        let mut harness = Harness::new("[present]\no\n.\n~\nP\n");
+        harness.tap(KeyCode::H);
+        harness.advance(50);
+        let pot = harness
+            .app
+            .world
+            .query_filtered::<&Transform, With<Weight>>()
+            .single(&harness.app.world)
+            .translation;
+        assert_eq!(pot.truncate(), Vec2::new(0.0, TILE_SIZE));
+        assert_eq!(harness.player_translation().y, 0.0);
+        assert!(!harness.app.world.resource::<HookshotFiring>().0);
+        assert_eq!(harness.count::<Hookshot>(), 0);
+    }
+
+    #[test]
+    fn test_rewind_puts_a_dragged_pot_back() {
+        let mut harness = Harness::new("[present]\no\n.\n~\nP\n");
+        harness.tap(KeyCode::H);
+        harness.advance(30);
+        harness.press(KeyCode::R);
+        harness.advance(40);
+        let pot = harness
+            .app
+            .world
+            .query_filtered::<&Transform, With<Weight>>()
+            .single(&harness.app.world)
+            .translation;
+        assert_eq!(pot.truncate(), Vec2::new(0.0, 3.0 * TILE_SIZE));
+    }
+
+    #[test]
+    fn test_echo_replays_the_hookshot() {
+        let mut harness = Harness::new("[present]\nH\n.\n~\nP\n");
+        harness.tap(KeyCode::E);
+        harness.tap(KeyCode::H);
+        harness.advance(50);
+        harness.tap(KeyCode::E);
+        assert_eq!(harness.count::<Echo>(), 1);
+
+        harness.advance(16);
+        assert_eq!(harness.count::<HookshotHitBlock>(), 1);
+        assert!(!harness.app.world.resource::<HookshotFiring>().0);
+        harness.advance(30);
+        let echo = harness
+            .app
+            .world
+            .query_filtered::<&Transform, With<Echo>>()
+            .single(&harness.app.world)
+            .translation;
+        assert_eq!(echo.y, 2.0 * TILE_SIZE);
+        assert_eq!(harness.count::<Hookshot>(), 0);
+    }
+
+    #[test]
+    fn test_hookshot_comes_back_from_walls_and_out_of_range() {
+        for level in ["[present]\n#\n.\n.\nP\n", OPEN_LEVEL] {
+            let mut harness = Harness::new(level);
+            harness.tap(KeyCode::H);
+            harness.advance(20);
+            assert!(harness.app.world.resource::<HookshotFiring>().0);
+            assert_eq!(harness.count::<Hookshot>(), 1);
+
+            harness.advance(40);
+            assert!(!harness.app.world.resource::<HookshotFiring>().0);
+            assert_eq!(harness.count::<Hookshot>(), 0);
+            assert_eq!(harness.count::<HookshotHitBlock>(), 0);
+            assert_eq!(harness.player_translation().y, 0.0);
+        }
+    }
+
+    #[test]
+    fn test_direction_pressed_while_moving_is_taken_on_the_next_tile() {
+        let mut harness = Harness::new(OPEN_LEVEL);
+        harness.press(KeyCode::D);
+        harness.advance(20);
+        harness.release(KeyCode::D);
+        harness.tap(KeyCode::W);
+        harness.advance(80);
+        assert_eq!(
+            harness.player_translation().truncate(),
+            Vec2::new(TILE_SIZE, TILE_SIZE)
+        );
+    }
+
+    #[test]
+    fn test_fire_pressed_during_cooldown_fires_once_it_ends() {
+        let mut harness = Harness::new(OPEN_LEVEL);
+        // Sideways, so the first arrow is still on screen at the end.
+        harness.tap(KeyCode::D);
+        harness.tap(KeyCode::J);
+        harness.advance(20);
+        harness.tap(KeyCode::J);
+        assert_eq!(harness.count::<Arrow>(), 1);
+        harness.advance(12);
+        assert_eq!(harness.count::<Arrow>(), 2);
+    }
+
+    #[test]
+    fn test_presses_during_a_rewind_are_not_buffered() {
+        let mut harness = Harness::new(OPEN_LEVEL);
+        harness.press(KeyCode::R);
+        harness.advance(2);
+        harness.tap(KeyCode::J);
+        harness.release(KeyCode::R);
+        harness.advance(5);
+        assert_eq!(harness.count::<Arrow>(), 0);
+    }
+
+    #[test]
+    fn test_gamepad_stick_walks_and_button_fires() {
+        let mut harness = Harness::new(OPEN_LEVEL);
+        harness.connect_gamepad();
+        harness.advance(1);
+        harness.send_gamepad(GamepadEventType::AxisChanged(
+            GamepadAxisType::LeftStickX,
+            0.9,
+        ));
+        harness.send_gamepad(GamepadEventType::AxisChanged(
+            GamepadAxisType::LeftStickY,
+            0.3,
+        ));
+        harness.advance(20);
+        harness.send_gamepad(GamepadEventType::AxisChanged(
+            GamepadAxisType::LeftStickX,
+            0.0,
+        ));
+        harness.advance(60);
+        assert_eq!(harness.player_translation().x, TILE_SIZE);
+        assert_eq!(harness.player_translation().y, 0.0);
+
+        let fire = GamepadButtonType::West;
+        harness.send_gamepad(GamepadEventType::ButtonChanged(fire, 1.0));
+        harness.advance(1);
+        harness.send_gamepad(GamepadEventType::ButtonChanged(fire, 0.0));
+        assert_eq!(harness.count::<Arrow>(), 1);
+    }
+
+    #[test]
+    fn test_same_presses_play_out_the_same() {
+        let play = || {
+            let mut harness = Harness::new(OPEN_LEVEL);
+            harness.press(KeyCode::W);
+            harness.advance(17);
+            harness.release(KeyCode::W);
+            harness.tap(KeyCode::A);
+            harness.press(KeyCode::A);
+            harness.advance(23);
+            harness.player_translation()
+        };
+        assert_eq!(play(), play());
+    }
+
+    #[test]
+    fn test_played_back_input_matches_live_input() {
+        let mut live = Harness::new(OPEN_LEVEL);
+        live.press(KeyCode::D);
+        live.advance(20);
+        live.release(KeyCode::D);
+        live.tap(KeyCode::J);
+        live.advance(10);
+
+        let source = (0..20)
+            .map(|tick| format!("{} right\n", tick))
+            .collect::<String>()
+            + "20 fire\n";
+        let mut played = Harness::new(OPEN_LEVEL);
+        played
+            .app
+            .insert_resource(InputPlayback(Recording::parse(&source).unwrap()));
+        played.advance(31);
+
+        assert_eq!(played.player_translation(), live.player_translation());
+        assert_eq!(played.count::<Arrow>(), 1);
+        assert_eq!(live.count::<Arrow>(), 1);
+    }
+
+    #[test]
+    fn test_time_travel_on_release() {
+        let mut harness = Harness::new("[present]\nP\n[future]\n.\n");
+        harness.tap(KeyCode::T);
+        assert_eq!(
+            harness.app.world.resource::<CurrentEra>().current_era,
+            Era::Present
+        );
+        harness.advance(1);
+        assert_eq!(
+            harness.app.world.resource::<CurrentEra>().current_era,
+            Era::Future
+        );
+    }
+
+    #[test]
+    fn test_rewind_returns_to_the_era_travelled_from() {
+        let mut harness = Harness::new("[present]\nP\n[future]\n.\n");
+        harness.advance(5);
+        harness.tap(KeyCode::T);
+        harness.advance(5);
+        assert_eq!(
+            harness.app.world.resource::<CurrentEra>().current_era,
+            Era::Future
+        );
+
+        harness.press(KeyCode::R);
+        harness.advance(10);
+        assert_eq!(
+            harness.app.world.resource::<CurrentEra>().current_era,
+            Era::Present
+        );
+        harness.tap(KeyCode::T);
+        harness.advance(1);
+        assert_eq!(
+            harness.app.world.resource::<CurrentEra>().current_era,
+            Era::Present
+        );
+    }
+
+    #[test]
+    fn test_closing_settings_leaves_a_paused_tick_paused() {
+        let mut harness = Harness::new(OPEN_LEVEL);
+        let mut timesteps = harness.app.world.resource_mut::<FixedTimesteps>();
+        timesteps.get_mut(TICK).unwrap().pause();
+        harness.tap(KeyCode::F2);
+        harness.tap(KeyCode::F2);
+        let timesteps = harness.app.world.resource::<FixedTimesteps>();
+        assert!(timesteps.get(TICK).unwrap().paused);
+    }
+
+    #[test]
+    fn test_rewinding_a_trip_to_the_past_leaves_its_effects_pending() {
+        let mut harness = Harness::new("[past]\n.I\n..\n[present]\n.I\n.P\n");
+        harness.tap(KeyCode::G);
+        harness.advance(5);
+        harness.tap(KeyCode::I);
+        harness.advance(5);
+        assert_eq!(harness.count::<InteractedWith>(), 1);
+
+        harness.press(KeyCode::R);
+        harness.advance(20);
+        harness.release(KeyCode::R);
+        harness.advance(5);
+        assert_eq!(
+            harness.app.world.resource::<CurrentEra>().current_era,
+            Era::Present
+        );
+        let present_switch_is_untouched = harness
+            .app
+            .world
+            .query_filtered::<&InEras, With<InteractedWith>>()
+            .iter(&harness.app.world)
+            .all(|in_eras| !in_eras.contains(Era::Present));
+        assert!(present_switch_is_untouched);
+    }
+
+    #[test]
+    fn test_level_reload_leaves_the_settings_menu_paused() {
+        let mut harness = Harness::new(OPEN_LEVEL);
+        harness.tap(KeyCode::F2);
+        let handle = harness.app.world.resource::<CurrentLevel>().handle.clone();
+        harness
+            .app
+            .world
+            .resource_mut::<Assets<Level>>()
+            .get_mut(&handle)
+            .unwrap();
+        harness.advance(2);
+        let timesteps = harness.app.world.resource::<FixedTimesteps>();
+        assert!(timesteps.get(TICK).unwrap().paused);
+
+        harness.tap(KeyCode::F2);
+        let timesteps = harness.app.world.resource::<FixedTimesteps>();
+        assert!(!timesteps.get(TICK).unwrap().paused);
+    }
+
+    #[test]
+    fn test_settings_rebind_movement_and_report_conflicts() {
+        let mut harness = Harness::new(OPEN_LEVEL);
+        harness.tap(KeyCode::F2);
+        for key in [KeyCode::Down, KeyCode::Down, KeyCode::Down, KeyCode::Return] {
+            harness.tap(key);
+        }
+        harness.tap(KeyCode::L);
+        assert_eq!(
+            harness.app.world.resource::<KeyBindings>().right,
+            KeyCode::L
+        );
+
+        harness.tap(KeyCode::Down);
+        harness.tap(KeyCode::Return);
+        harness.tap(KeyCode::J);
+        assert!(harness
+            .app
+            .world
+            .resource::<SettingsMenu>()
+            .conflict
+            .is_some());
+        assert_eq!(
+            harness.app.world.resource::<KeyBindings>().interact,
+            KeyCode::I
+        );
+        harness.tap(KeyCode::Back);
+        harness.tap(KeyCode::F2);
+
+        harness.press(KeyCode::L);
+        harness.advance(20);
+        assert!(harness.player_translation().x > 0.0);
+    }
+}
@@ -0,0 +1,184 @@
+use bevy::asset::FileAssetIo;
+use bevy::window::CursorMoved;
+
+use crate::levelmod::level::{tile_position, Level};
+use crate::*;
+
+pub struct EditorPlugin;
+
+impl Plugin for EditorPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Editing(false))
+            .add_startup_system(spawn_editor_cursor)
+            .add_system(
+                toggle_editor
+                    .run_if_not(is_in_settings)
+                    .label("editortoggle"),
+            )
+            .add_system(
+                move_editor_cursor
+                    .run_if(is_editing)
+                    .label("editorcursor")
+                    .after("editortoggle"),
+            )
+            .add_system(paint_level.run_if(is_editing).after("editorcursor"))
+            .add_system(save_level.run_if(is_editing));
+    }
+}
+
+/// Keys that pick the brush, in the order of [`LevelTile::ALL`].
+const BRUSH_KEYS: [KeyCode; 10] = [
+    KeyCode::Key1,
+    KeyCode::Key2,
+    KeyCode::Key3,
+    KeyCode::Key4,
+    KeyCode::Key5,
+    KeyCode::Key6,
+    KeyCode::Key7,
+    KeyCode::Key8,
+    KeyCode::Key9,
+    KeyCode::Key0,
+];
+
+#[derive(Default, Resource)]
+pub struct Editing(pub bool);
+
+pub fn is_editing(editing: Res<Editing>) -> bool {
+    editing.0
+}
+
+/// The tile the editor is pointing at and the tile it places there.
+#[derive(Component)]
+pub struct EditorCursor {
+    position: (i32, i32),
+    brush: LevelTile,
+}
+
+fn spawn_editor_cursor(mut commands: Commands) {
+    let shape = shapes::Rectangle {
+        extents: Vec2::new(TILE_SIZE, TILE_SIZE),
+        origin: RectangleOrigin::Center,
+    };
+    let mut cursor = GeometryBuilder::build_as(
+        &shape,
+        DrawMode::Stroke(StrokeMode::new(Color::BLACK, TILE_SIZE / 10.0)),
+        Transform::from_translation(Vec3::new(0.0, 0.0, PLAYER_LEVEL + 100.0)),
+    );
+    cursor.visibility.is_visible = false;
+    commands.spawn((
+        cursor,
+        EditorCursor {
+            position: (0, 0),
+            brush: LevelTile::Block,
+        },
+    ));
+}
+
+/// Switches between playing and editing, showing the cursor
+/// on the player's tile whenever the editor opens.
+fn toggle_editor(
+    mut cursor_query: Query<(&mut EditorCursor, &mut Visibility)>,
+    player_query: Query<&Transform, With<Player>>,
+    keyboard: Res<Input<KeyCode>>,
+    key_bindings: Res<KeyBindings>,
+    mut editing: ResMut<Editing>,
+) {
+    if !keyboard.just_pressed(key_bindings.editor) {
+        return;
+    }
+    editing.0 = !editing.0;
+    let (mut cursor, mut visibility) = cursor_query.single_mut();
+    visibility.is_visible = editing.0;
+    if editing.0 {
+        cursor.position = tile_position(player_query.single().translation);
+    }
+}
+
+/// Snaps the cursor to the tile under the mouse, or steps
+/// it one tile at a time with the movement keys.
+fn move_editor_cursor(
+    mut cursor_query: Query<(&mut EditorCursor, &mut Transform)>,
+    camera_query: Query<(&Camera, &GlobalTransform)>,
+    keyboard: Res<Input<KeyCode>>,
+    key_bindings: Res<KeyBindings>,
+    mut cursor_moved: EventReader<CursorMoved>,
+) {
+    let (mut cursor, mut cursor_transform) = cursor_query.single_mut();
+    if let Some(cursor_moved) = cursor_moved.iter().last() {
+        let (camera, camera_transform) = camera_query.single();
+        if let Some(ray) = camera.viewport_to_world(camera_transform, cursor_moved.position) {
+            cursor.position = tile_position(ray.origin);
+        }
+    }
+    for (key, (x, y)) in [
+        (key_bindings.up, (0, 1)),
+        (key_bindings.down, (0, -1)),
+        (key_bindings.left, (-1, 0)),
+        (key_bindings.right, (1, 0)),
+    ] {
+        if keyboard.just_pressed(key) {
+            cursor.position.0 += x;
+            cursor.position.1 += y;
+        }
+    }
+    cursor_transform.translation.x = cursor.position.0 as f32 * TILE_SIZE;
+    cursor_transform.translation.y = cursor.position.1 as f32 * TILE_SIZE;
+}
+
+/// Places or erases tiles in the current era. Editing the level asset
+/// respawns it through the same path as a reload from disk.
+fn paint_level(
+    mut cursor_query: Query<&mut EditorCursor>,
+    keyboard: Res<Input<KeyCode>>,
+    mouse: Res<Input<MouseButton>>,
+    key_bindings: Res<KeyBindings>,
+    current_level: Res<CurrentLevel>,
+    current_era: Res<CurrentEra>,
+    mut levels: ResMut<Assets<Level>>,
+) {
+    let mut cursor = cursor_query.single_mut();
+    for (key, tile) in BRUSH_KEYS.into_iter().zip(LevelTile::ALL) {
+        if keyboard.just_pressed(key) {
+            cursor.brush = tile;
+            info!("Editor brush: {:?}", tile);
+        }
+    }
+
+    let is_placing =
+        keyboard.just_pressed(key_bindings.interact) || mouse.just_pressed(MouseButton::Left);
+    let is_erasing =
+        keyboard.just_pressed(key_bindings.fire) || mouse.just_pressed(MouseButton::Right);
+    if !is_placing && !is_erasing {
+        return;
+    }
+    let Some(level) = levels.get_mut(&current_level.handle) else {
+        return;
+    };
+    if is_placing {
+        level.set_tile(current_era.current_era, cursor.position, cursor.brush);
+    } else {
+        level.erase_tile(current_era.current_era, cursor.position);
+    }
+}
+
+/// Writes the edited level back to the file it was loaded from.
+fn save_level(
+    keyboard: Res<Input<KeyCode>>,
+    key_bindings: Res<KeyBindings>,
+    current_level: Res<CurrentLevel>,
+    levels: Res<Assets<Level>>,
+) {
+    if !keyboard.just_pressed(key_bindings.save_level) {
+        return;
+    }
+    let Some(level) = levels.get(&current_level.handle) else {
+        return;
+    };
+    let path = FileAssetIo::get_base_path()
+        .join(ASSET_FOLDER)
+        .join(current_level.path);
+    match std::fs::write(&path, level.to_string()) {
+        Ok(()) => info!("Saved level to {}", path.display()),
+        Err(error) => error!("Could not save level to {}: {}", path.display(), error),
+    }
+}
@@ -0,0 +1,430 @@
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::resources::{Era, COMMENT};
+use crate::TILE_SIZE;
+
+/// Marks every entity spawned from a level file.
+#[derive(Component)]
+pub struct LevelEntity;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LevelTile {
+    Floor,
+    Wall,
+    Water,
+    Grate,
+    Switch,
+    HookshotTarget,
+    Block,
+    Sapling,
+    Tree,
+    Pot,
+}
+
+impl LevelTile {
+    pub const ALL: [LevelTile; 10] = [
+        LevelTile::Floor,
+        LevelTile::Wall,
+        LevelTile::Water,
+        LevelTile::Grate,
+        LevelTile::Switch,
+        LevelTile::HookshotTarget,
+        LevelTile::Block,
+        LevelTile::Sapling,
+        LevelTile::Tree,
+        LevelTile::Pot,
+    ];
+
+    pub fn from_char(character: char) -> Option<LevelTile> {
+        LevelTile::ALL
+            .into_iter()
+            .find(|tile| tile.to_char() == character)
+    }
+
+    pub fn to_char(self) -> char {
+        match self {
+            LevelTile::Floor => '.',
+            LevelTile::Wall => '#',
+            LevelTile::Water => '~',
+            LevelTile::Grate => '=',
+            LevelTile::Switch => 'I',
+            LevelTile::HookshotTarget => 'H',
+            LevelTile::Block => 'B',
+            LevelTile::Sapling => 's',
+            LevelTile::Tree => 'T',
+            LevelTile::Pot => 'o',
+        }
+    }
+}
+
+const SPAWN: char = 'P';
+const EMPTY: char = ' ';
+
+fn era_name(era: Era) -> &'static str {
+    match era {
+        Era::Past => "past",
+        Era::Present => "present",
+        Era::Future => "future",
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum LevelError {
+    UnknownEra { line: usize, name: String },
+    DuplicateEra { line: usize, era: Era },
+    UnknownTile { line: usize, character: char },
+    TileOutsideEra { line: usize },
+    MissingSpawn,
+    MultipleSpawns { line: usize },
+}
+
+impl fmt::Display for LevelError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LevelError::UnknownEra { line, name } => {
+                write!(f, "line {}: unknown era [{}]", line, name)
+            }
+            LevelError::DuplicateEra { line, era } => {
+                write!(f, "line {}: era {:?} is described twice", line, era)
+            }
+            LevelError::UnknownTile { line, character } => {
+                write!(f, "line {}: unknown tile '{}'", line, character)
+            }
+            LevelError::TileOutsideEra { line } => {
+                write!(f, "line {}: tiles must follow an era header", line)
+            }
+            LevelError::MissingSpawn => write!(f, "level has no player spawn '{}'", SPAWN),
+            LevelError::MultipleSpawns { line } => {
+                write!(f, "line {}: level has more than one player spawn", line)
+            }
+        }
+    }
+}
+
+impl std::error::Error for LevelError {}
+
+/// A level read from a `.level` file. Each era section is an ASCII
+/// grid, top row first, and all sections share the same top-left
+/// corner. Tile positions are in tiles relative to the player spawn.
+#[derive(Clone, Debug, PartialEq, TypeUuid)]
+#[uuid = "7d6c8f6e-3c1b-4c55-9a53-2f4e5b0a9d41"]
+pub struct Level {
+    /// The comment lines above the first era, kept so that
+    /// saving the level doesn't lose them.
+    pub comments: Vec<String>,
+    pub spawn_era: Era,
+    pub tiles: BTreeMap<Era, BTreeMap<(i32, i32), LevelTile>>,
+}
+
+impl Level {
+    pub fn parse(source: &str) -> Result<Level, LevelError> {
+        let mut rows: BTreeMap<Era, Vec<(usize, &str)>> = BTreeMap::new();
+        let mut current_era = None;
+        let mut comments = Vec::new();
+        for (index, line) in source.lines().enumerate() {
+            let line_number = index + 1;
+            if line.starts_with(COMMENT) {
+                if current_era.is_none() {
+                    comments.push(line.to_string());
+                }
+                continue;
+            }
+            let trimmed = line.trim();
+            if trimmed.starts_with('[') && trimmed.ends_with(']') {
+                let name = &trimmed[1..trimmed.len() - 1];
+                let era = Era::ALL
+                    .into_iter()
+                    .find(|era| era_name(*era) == name)
+                    .ok_or_else(|| LevelError::UnknownEra {
+                        line: line_number,
+                        name: name.to_string(),
+                    })?;
+                if rows.insert(era, Vec::new()).is_some() {
+                    return Err(LevelError::DuplicateEra {
+                        line: line_number,
+                        era,
+                    });
+                }
+                current_era = Some(era);
+                continue;
+            }
+            match current_era {
+                Some(era) => rows.entry(era).or_default().push((line_number, line)),
+                None if trimmed.is_empty() => {}
+                None => return Err(LevelError::TileOutsideEra { line: line_number }),
+            }
+        }
+
+        let mut spawn = None;
+        let mut grid_tiles = BTreeMap::new();
+        for (era, era_rows) in rows {
+            let mut era_tiles = BTreeMap::new();
+            for (row, (line_number, line)) in era_rows.into_iter().enumerate() {
+                for (column, character) in line.chars().enumerate() {
+                    let position = (column as i32, row as i32);
+                    let tile = match character {
+                        EMPTY => continue,
+                        SPAWN => {
+                            if spawn.is_some() {
+                                return Err(LevelError::MultipleSpawns { line: line_number });
+                            }
+                            spawn = Some((era, position));
+                            LevelTile::Floor
+                        }
+                        _ => LevelTile::from_char(character).ok_or(LevelError::UnknownTile {
+                            line: line_number,
+                            character,
+                        })?,
+                    };
+                    era_tiles.insert(position, tile);
+                }
+            }
+            grid_tiles.insert(era, era_tiles);
+        }
+
+        let (spawn_era, (spawn_column, spawn_row)) = spawn.ok_or(LevelError::MissingSpawn)?;
+        let tiles = grid_tiles
+            .into_iter()
+            .map(|(era, era_tiles)| {
+                let era_tiles = era_tiles
+                    .into_iter()
+                    .map(|((column, row), tile)| ((column - spawn_column, spawn_row - row), tile))
+                    .collect();
+                (era, era_tiles)
+            })
+            .collect();
+        Ok(Level {
+            comments,
+            spawn_era,
+            tiles,
+        })
+    }
+
+    pub fn era_tiles(&self, era: Era) -> impl Iterator<Item = ((i32, i32), LevelTile)> + '_ {
+        self.tiles
+            .get(&era)
+            .into_iter()
+            .flat_map(|era_tiles| era_tiles.iter().map(|(position, tile)| (*position, *tile)))
+    }
+
+    /// Puts a tile on a position in one era. Only floor
+    /// can be placed on the player spawn.
+    pub fn set_tile(&mut self, era: Era, position: (i32, i32), tile: LevelTile) {
+        if self.is_spawn(era, position) && tile != LevelTile::Floor {
+            return;
+        }
+        self.tiles.entry(era).or_default().insert(position, tile);
+    }
+
+    /// Clears a tile down to bare floor, or removes bare floor
+    /// altogether. The player spawn always keeps its floor.
+    pub fn erase_tile(&mut self, era: Era, position: (i32, i32)) {
+        let is_spawn = self.is_spawn(era, position);
+        let Some(era_tiles) = self.tiles.get_mut(&era) else {
+            return;
+        };
+        match era_tiles.get(&position) {
+            Some(LevelTile::Floor) if !is_spawn => {
+                era_tiles.remove(&position);
+            }
+            Some(_) => {
+                era_tiles.insert(position, LevelTile::Floor);
+            }
+            None => {}
+        }
+    }
+
+    fn is_spawn(&self, era: Era, position: (i32, i32)) -> bool {
+        (era, position) == (self.spawn_era, (0, 0))
+    }
+
+    /// Whether the player can stand on a tile, which only
+    /// bare floor allows.
+    pub fn is_open(&self, era: Era, position: (i32, i32)) -> bool {
+        self.tiles
+            .get(&era)
+            .and_then(|era_tiles| era_tiles.get(&position))
+            == Some(&LevelTile::Floor)
+    }
+}
+
+/// The level tile a world translation falls on.
+pub fn tile_position(translation: Vec3) -> (i32, i32) {
+    (
+        (translation.x / TILE_SIZE).round() as i32,
+        (translation.y / TILE_SIZE).round() as i32,
+    )
+}
+
+impl fmt::Display for Level {
+    /// Writes the level back out in the format read by [`Level::parse`].
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for comment in &self.comments {
+            writeln!(f, "{}", comment)?;
+        }
+        let positions = self
+            .tiles
+            .values()
+            .flat_map(|era_tiles| era_tiles.keys())
+            .chain(std::iter::once(&(0, 0)));
+        let (mut min_x, mut max_x, mut max_y, mut min_y) = (0, 0, 0, 0);
+        for (x, y) in positions {
+            min_x = min_x.min(*x);
+            max_x = max_x.max(*x);
+            min_y = min_y.min(*y);
+            max_y = max_y.max(*y);
+        }
+
+        let mut eras: Vec<Era> = self.tiles.keys().copied().collect();
+        if !eras.contains(&self.spawn_era) {
+            eras.push(self.spawn_era);
+            eras.sort();
+        }
+        for era in eras {
+            writeln!(f, "[{}]", era_name(era))?;
+            let era_tiles = self.tiles.get(&era);
+            for y in (min_y..=max_y).rev() {
+                let row: String = (min_x..=max_x)
+                    .map(|x| {
+                        if era == self.spawn_era && (x, y) == (0, 0) {
+                            SPAWN
+                        } else {
+                            era_tiles
+                                .and_then(|era_tiles| era_tiles.get(&(x, y)))
+                                .map_or(EMPTY, |tile| tile.to_char())
+                        }
+                    })
+                    .collect();
+                writeln!(f, "{}", row.trim_end())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test_level {
+    use super::*;
+
+    #[test]
+    fn test_parse_positions_are_relative_to_spawn() {
+        let level = Level::parse("[present]\n#..\n.P.\n..H\n").unwrap();
+        assert_eq!(level.spawn_era, Era::Present);
+        let tiles = &level.tiles[&Era::Present];
+        assert_eq!(tiles[&(-1, 1)], LevelTile::Wall);
+        assert_eq!(tiles[&(0, 0)], LevelTile::Floor);
+        assert_eq!(tiles[&(1, -1)], LevelTile::HookshotTarget);
+        assert_eq!(tiles.len(), 9);
+    }
+
+    #[test]
+    fn test_parse_eras_share_top_left_corner() {
+        let level = Level::parse("; comment\n[past]\n  s\n[present]\n.P\n  T\n").unwrap();
+        assert_eq!(level.tiles[&Era::Past][&(1, 0)], LevelTile::Sapling);
+        assert_eq!(level.tiles[&Era::Present][&(1, -1)], LevelTile::Tree);
+        assert!(!level.tiles[&Era::Past].contains_key(&(0, 0)));
+    }
+
+    #[test]
+    fn test_parse_missing_spawn() {
+        assert_eq!(Level::parse("[past]\n...\n"), Err(LevelError::MissingSpawn));
+    }
+
+    #[test]
+    fn test_parse_multiple_spawns() {
+        assert_eq!(
+            Level::parse("[past]\nP\n[future]\nP\n"),
+            Err(LevelError::MultipleSpawns { line: 4 })
+        );
+    }
+
+    #[test]
+    fn test_parse_unknown_tile() {
+        assert_eq!(
+            Level::parse("[past]\nP?\n"),
+            Err(LevelError::UnknownTile {
+                line: 2,
+                character: '?'
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_unknown_era() {
+        assert_eq!(
+            Level::parse("[jurassic]\nP\n"),
+            Err(LevelError::UnknownEra {
+                line: 1,
+                name: "jurassic".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_tiles_outside_era() {
+        assert_eq!(
+            Level::parse("P\n"),
+            Err(LevelError::TileOutsideEra { line: 1 })
+        );
+    }
+
+    #[test]
+    fn test_is_open_only_on_floor() {
+        let level = Level::parse("[past]\n.#\n[present]\nP\n").unwrap();
+        assert!(level.is_open(Era::Past, (0, 0)));
+        assert!(!level.is_open(Era::Past, (1, 0)));
+        assert!(!level.is_open(Era::Past, (0, -1)));
+        assert!(level.is_open(Era::Present, (0, 0)));
+        assert!(!level.is_open(Era::Future, (0, 0)));
+    }
+
+    #[test]
+    fn test_set_tile_keeps_spawn_clear() {
+        let mut level = Level::parse("[present]\nP.\n").unwrap();
+        level.set_tile(Era::Present, (0, 0), LevelTile::Wall);
+        level.set_tile(Era::Present, (1, 0), LevelTile::Wall);
+        level.set_tile(Era::Past, (0, 0), LevelTile::Switch);
+        assert_eq!(level.tiles[&Era::Present][&(0, 0)], LevelTile::Floor);
+        assert_eq!(level.tiles[&Era::Present][&(1, 0)], LevelTile::Wall);
+        assert_eq!(level.tiles[&Era::Past][&(0, 0)], LevelTile::Switch);
+    }
+
+    #[test]
+    fn test_erase_tile_clears_to_floor_then_removes() {
+        let mut level = Level::parse("[present]\nPB\n").unwrap();
+        level.erase_tile(Era::Present, (1, 0));
+        assert_eq!(level.tiles[&Era::Present][&(1, 0)], LevelTile::Floor);
+        level.erase_tile(Era::Present, (1, 0));
+        assert!(!level.tiles[&Era::Present].contains_key(&(1, 0)));
+        level.erase_tile(Era::Present, (0, 0));
+        assert_eq!(level.tiles[&Era::Present][&(0, 0)], LevelTile::Floor);
+    }
+
+    #[test]
+    fn test_tile_position_rounds_to_nearest_tile() {
+        assert_eq!(
+            tile_position(Vec3::new(2.4 * TILE_SIZE, -1.6 * TILE_SIZE, 0.0)),
+            (2, -2)
+        );
+        assert_eq!(tile_position(Vec3::new(-0.4 * TILE_SIZE, 0.0, 0.0)), (0, 0));
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        let source = "[past]\n  s\n..#\n[present]\nBP\n..I\n[future]\n\n  T\n";
+        let level = Level::parse(source).unwrap();
+        assert_eq!(level.to_string(), source);
+        assert_eq!(Level::parse(&level.to_string()).unwrap(), level);
+    }
+
+    #[test]
+    fn test_display_round_trip_keeps_leading_comments() {
+        let source = "; Tiles: . floor, # wall\n; P player spawn\n[present]\n#P\n";
+        let level = Level::parse(source).unwrap();
+        assert_eq!(level.comments.len(), 2);
+        assert_eq!(level.to_string(), source);
+    }
+}
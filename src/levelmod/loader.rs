@@ -0,0 +1,157 @@
+use bevy::asset::{AssetLoader, BoxedFuture, LoadContext, LoadedAsset};
+use bevy::utils::HashMap;
+
+use crate::background::draw_floor;
+use crate::events::EraChangedEvent;
+use crate::levelmod::level::{tile_position, Level};
+use crate::*;
+
+pub struct LevelPlugin;
+
+impl Plugin for LevelPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<Level>()
+            .init_asset_loader::<LevelLoader>()
+            .add_startup_system(load_level)
+            .add_system(spawn_level.run_on_event::<AssetEvent<Level>>());
+    }
+}
+
+pub const ASSET_FOLDER: &str = "assets";
+
+/// Level loaded on startup, relative to the assets folder.
+const STARTING_LEVEL: &str = "levels/first.level";
+
+#[derive(Default)]
+pub struct LevelLoader;
+
+impl AssetLoader for LevelLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let level = Level::parse(std::str::from_utf8(bytes)?)?;
+            load_context.set_default_asset(LoadedAsset::new(level));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["level"]
+    }
+}
+
+#[derive(Resource)]
+pub struct CurrentLevel {
+    pub handle: Handle<Level>,
+    pub path: &'static str,
+}
+
+/// Loads the starting level, unless a level was put in place before startup.
+fn load_level(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    current_level: Option<Res<CurrentLevel>>,
+) {
+    if current_level.is_some() {
+        return;
+    }
+    commands.insert_resource(CurrentLevel {
+        handle: asset_server.load(STARTING_LEVEL),
+        path: STARTING_LEVEL,
+    });
+}
+
+/// Spawns the current level once it has loaded, and respawns it whenever
+/// the file changes on disk. A reload keeps the player where they are if
+/// their tile is still open floor in the current era, and otherwise puts
+/// them back on the spawn tile in the spawn era. Ticks start on the first
+/// load, or once the settings menu closes if it is open by then.
+fn spawn_level(
+    mut commands: Commands,
+    level_entity_query: Query<Entity, With<LevelEntity>>,
+    mut player_query: Query<&mut Transform, With<Player>>,
+    levels: Res<Assets<Level>>,
+    current_level: Res<CurrentLevel>,
+    mut current_era: ResMut<CurrentEra>,
+    mut event: EventReader<AssetEvent<Level>>,
+    mut era_event_writer: EventWriter<EraChangedEvent>,
+    mut timesteps: ResMut<FixedTimesteps>,
+    mut settings_menu: ResMut<SettingsMenu>,
+) {
+    let mut is_loaded = false;
+    let mut is_reloaded = false;
+    for asset_event in event.iter() {
+        match asset_event {
+            AssetEvent::Created { handle } => is_loaded |= *handle == current_level.handle,
+            AssetEvent::Modified { handle } => is_reloaded |= *handle == current_level.handle,
+            AssetEvent::Removed { .. } => {}
+        }
+    }
+    if !is_loaded && !is_reloaded {
+        return;
+    }
+    let Some(level) = levels.get(&current_level.handle) else {
+        return;
+    };
+
+    for entity in level_entity_query.iter() {
+        if let Some(level_entity) = commands.get_entity(entity) {
+            level_entity.despawn_recursive();
+        }
+    }
+    spawn_level_entities(&mut commands, level, &current_era);
+    if is_loaded {
+        if settings_menu.is_open {
+            settings_menu.was_ticking = true;
+        } else {
+            start_ticking(&mut timesteps);
+        }
+    }
+
+    for mut player_transform in player_query.iter_mut() {
+        let player_tile = tile_position(player_transform.translation);
+        if is_reloaded && level.is_open(current_era.current_era, player_tile) {
+            continue;
+        }
+        player_transform.translation.x = 0.0;
+        player_transform.translation.y = 0.0;
+        let previous_era = current_era.current_era;
+        if previous_era != level.spawn_era {
+            current_era.current_era = level.spawn_era;
+            era_event_writer.send(EraChangedEvent::new(previous_era, level.spawn_era));
+        }
+    }
+}
+
+fn spawn_level_entities(commands: &mut Commands, level: &Level, current_era: &CurrentEra) {
+    let mut spawned = HashMap::default();
+    for era in Era::ALL {
+        if let Some(era_root) = current_era.root(era) {
+            draw_floor(
+                commands,
+                era_root,
+                era,
+                level.era_tiles(era).map(|(position, _)| position),
+            );
+        }
+        for ((x, y), tile) in level.era_tiles(era) {
+            let location = Vec2::new(x as f32 * TILE_SIZE, y as f32 * TILE_SIZE);
+            if let Some(entity) = spawn_level_tile(commands, tile, era, location) {
+                spawned.insert((era, (x, y)), entity);
+            }
+        }
+    }
+    // Whatever stands on a tile in one era becomes
+    // whatever stands on the same tile in the next.
+    for ((era, position), ancestor) in spawned.iter() {
+        if let Some(descendant) = era
+            .next()
+            .and_then(|next_era| spawned.get(&(next_era, *position)))
+        {
+            commands.entity(*ancestor).insert(Descendant(*descendant));
+        }
+    }
+}
@@ -0,0 +1,16 @@
+use crate::*;
+use bevy::app::PluginGroupBuilder;
+
+pub mod editor;
+pub mod level;
+pub mod loader;
+
+pub struct LevelModPluginGroup;
+
+impl PluginGroup for LevelModPluginGroup {
+    fn build(self) -> PluginGroupBuilder {
+        PluginGroupBuilder::start::<Self>()
+            .add(LevelPlugin)
+            .add(EditorPlugin)
+    }
+}
@@ -1,4 +1,4 @@
-#![allow(clippy::redundant_field_names, clippy::type_complexity)]
+#![allow(clippy::redundant_field_names, clippy::too_many_arguments, clippy::type_complexity)]
 #![allow(unused_imports)]
 use bevy::sprite::collide_aabb::collide;
 use bevy::{prelude::*, window::close_on_esc};
@@ -17,51 +17,96 @@ pub const PLAYER_LEVEL: f32 = 200.0;
 
 mod background;
 mod camera;
+mod causality;
 mod collisionsmod;
 mod events;
+#[cfg(test)]
+mod harness;
+mod levelmod;
 mod playermod;
 mod resources;
+mod settings;
 mod systemsmod;
 
 use background::BackgroundPlugin;
 use camera::CameraPlugin;
+use causality::*;
 use collisionsmod::collisions::*;
 use collisionsmod::components::*;
+use collisionsmod::grid::*;
 use collisionsmod::*;
 use events::{EventPlugin, InteractionEvent};
+use levelmod::editor::*;
+use levelmod::level::{LevelEntity, LevelTile};
+use levelmod::loader::*;
+use levelmod::*;
 use playermod::arrows::*;
+use playermod::echo::*;
 use playermod::hookshot::*;
 use playermod::player::*;
+use playermod::rewind::*;
 use playermod::*;
-use resources::KeyBindings;
+use resources::{
+    CurrentEra, Era, FrameInput, GamepadBindings, InputBuffer, KeyBindings, TickCount,
+    TickInput,
+};
+use settings::*;
+use systemsmod::controls::*;
 use systemsmod::general_systems::*;
+use systemsmod::recording::*;
+use systemsmod::tick::*;
 use systemsmod::*;
 
 fn main() {
+    let replay_mode = ReplayMode::from_args(std::env::args().skip(1)).unwrap_or_else(|error| {
+        eprintln!("{}", error);
+        std::process::exit(2);
+    });
     let height = 540.0;
-    App::new()
-        .insert_resource(Msaa { samples: 4 })
+    let mut app = App::new();
+    app.insert_resource(Msaa { samples: 4 })
         .insert_resource(ClearColor(Color::WHITE))
-        .add_plugins(DefaultPlugins.set(WindowPlugin {
-            window: WindowDescriptor {
-                width: height * RESOLUTION,
-                height: height,
-                title: "Time Travel".to_string(),
-                resizable: false,
-                ..Default::default()
-            },
-            ..Default::default()
-        }))
+        .add_plugins(
+            DefaultPlugins
+                .set(WindowPlugin {
+                    window: WindowDescriptor {
+                        width: height * RESOLUTION,
+                        height: height,
+                        title: "Time Travel".to_string(),
+                        resizable: false,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                })
+                .set(AssetPlugin {
+                    asset_folder: ASSET_FOLDER.to_string(),
+                    // Level files are reloaded as soon as they are saved.
+                    watch_for_changes: true,
+                }),
+        )
         // .add_plugin(LogDiagnosticsPlugin::default())
         // .add_plugin(FrameTimeDiagnosticsPlugin::default())
         .add_plugin(ShapePlugin)
+        .add_plugin(SettingsUiPlugin);
+    add_game_plugins(&mut app).add_system(close_on_esc);
+    if let Err(error) = replay_mode.start(&mut app) {
+        error!("{}", error);
+        std::process::exit(2);
+    }
+    app.run();
+}
+
+/// Adds the game itself. None of it needs a window or a renderer,
+/// so the headless harness runs the very same plugins.
+fn add_game_plugins(app: &mut App) -> &mut App {
+    // Adds the gameplay tick, so it goes before any plugin using it.
+    app.add_plugins(SystemsModPluginGroup)
         .add_plugin(CameraPlugin)
         .add_plugin(BackgroundPlugin)
-        .add_plugins(PlayerModPluginGroup)
-        .add_plugins(SystemsModPluginGroup)
         .add_plugins(CollisionsModPluginGroup)
+        .add_plugins(PlayerModPluginGroup)
+        .add_plugins(LevelModPluginGroup)
         .add_plugin(EventPlugin)
-        .add_system(close_on_esc)
-        .init_resource::<KeyBindings>()
-        .run();
+        .add_plugin(CausalityPlugin)
+        .add_plugin(SettingsPlugin)
 }
@@ -1,27 +1,48 @@
 use bevy::prelude::*;
+use bevy::utils::HashSet;
 use core::time::Duration;
 
+use crate::events::CollisionEvent;
 use crate::*;
 
 pub struct ArrowsPlugin;
 
 impl Plugin for ArrowsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_system(fire_arrow.label("firearrow"))
-            .add_startup_system(arrow_cooldown_init)
-            .add_system(arrow_move.label("arrowmove"))
-            .add_system(despawn_offscreen_arrows.after("arrowmove"))
-            .add_system(manage_arrow_collisions.after("arrowmove"))
-            .init_resource::<ArrowCooldown>();
+        app.add_fixed_timestep_system(
+            TICK,
+            GAMEPLAY_STAGE,
+            fire_arrow
+                .run_if_not(is_rewinding)
+                .run_if_not(is_editing)
+                .label("firearrow"),
+        )
+        .add_startup_system(arrow_cooldown_init)
+        .add_fixed_timestep_system(
+            TICK,
+            GAMEPLAY_STAGE,
+            arrow_move.run_if_not(is_rewinding).label("arrowmove"),
+        )
+        .add_fixed_timestep_system(
+            TICK,
+            GAMEPLAY_STAGE,
+            despawn_offscreen_arrows.after("arrowmove"),
+        )
+        .add_fixed_timestep_system(
+            TICK,
+            COLLISION_STAGE,
+            manage_arrow_collisions.after("collisiondetection"),
+        )
+        .init_resource::<ArrowCooldown>();
     }
 }
 
-const ARROW_COOLDOWN: f32 = 0.5;
+pub const ARROW_COOLDOWN: f32 = 0.5;
 const ARROW_SPEED: f32 = 7.5;
 const ARROW_LENGTH: f32 = TILE_SIZE / 2.0;
 const ARROW_WIDTH: f32 = TILE_SIZE / 10.0;
 
-#[derive(Component)]
+#[derive(Clone, Component, Copy)]
 pub struct Arrow {
     pub facing_direction: FacingDirection,
     pub speed: f32,
@@ -55,25 +76,27 @@ impl Default for ArrowCooldown {
     }
 }
 
-
+/// Breaks arrows on whatever they hit, along with
+/// anything they hit that can be destroyed.
 fn manage_arrow_collisions(
     mut commands: Commands,
-    mut arrow_query: Query<(&Transform, Entity, &Arrow), With<Arrow>>,
-    collidable_query: Query<(&Transform, Entity), With<Collidable>>,
+    arrow_query: Query<Entity, With<Arrow>>,
+    destructible_query: Query<Entity, With<Destructible>>,
+    mut event: EventReader<CollisionEvent>,
 ) {
-    let collidables: Vec<(Vec3, u32)> = collidable_query
-        .iter()
-        .map(|(transform, entity)| (transform.translation, entity.index()))
-        .collect();
-    for (arrow_transform, arrow_entity, arrow) in arrow_query.iter_mut() {
-        if check_collision(
-            &arrow_transform.translation,
-            &arrow_entity.index(),
-            &collidables,
-            arrow.size,
-        ).is_some() {
+    let mut broken_arrows = HashSet::new();
+    for collision_event in event.iter() {
+        let Ok(arrow_entity) = arrow_query.get(collision_event.entity()) else {
+            continue;
+        };
+        if broken_arrows.insert(arrow_entity) {
             commands.entity(arrow_entity).despawn();
         }
+        if let Ok(destructible_entity) = destructible_query.get(collision_event.other()) {
+            if let Some(destructible_entity) = commands.get_entity(destructible_entity) {
+                destructible_entity.despawn_recursive();
+            }
+        }
     }
 }
 
@@ -101,11 +124,12 @@ fn arrow_cooldown_init(mut cooldown_timer: ResMut<ArrowCooldown>) {
     cooldown_timer.tick(Duration::from_secs((ARROW_COOLDOWN + 1.0) as u64));
 }
 
-fn arrow_move(mut arrow_query: Query<(&mut Transform, &Arrow)>, time: Res<Time>) {
-    for (mut transform, arrow) in arrow_query.iter_mut() {
+fn arrow_move(mut arrow_query: Query<(&mut Transform, &mut Swept, &mut SubPixel, &Arrow)>) {
+    for (mut transform, mut swept, mut sub_pixel, arrow) in arrow_query.iter_mut() {
+        swept.previous_translation = transform.translation;
         let mut delta_x: f32 = 0.0;
         let mut delta_y: f32 = 0.0;
-        let movement_speed = get_manual_movement_speed(arrow.speed(), time.delta_seconds());
+        let movement_speed = sub_pixel.step(get_manual_movement_speed(arrow.speed(), TICK_SECONDS));
         match arrow.facing_direction() {
             FacingDirection::Up => delta_y += movement_speed,
             FacingDirection::Down => delta_y -= movement_speed,
@@ -119,59 +143,87 @@ fn arrow_move(mut arrow_query: Query<(&mut Transform, &Arrow)>, time: Res<Time>)
 
 fn fire_arrow(
     mut commands: Commands,
-    pdi_query: Query<(&mut GlobalTransform, &mut FacingDirection), With<PlayerDirectionIndicator>>,
-    keyboard: Res<Input<KeyCode>>,
-    key_bindings: Res<KeyBindings>,
-    time: Res<Time>,
+    player_query: Query<&Transform, With<Player>>,
+    pdi_query: Query<(&Transform, &FacingDirection), With<PlayerDirectionIndicator>>,
+    mut input_buffer: ResMut<InputBuffer>,
     mut arrow_cooldown: ResMut<ArrowCooldown>,
 ) {
-    arrow_cooldown.tick(time.delta());
-    if arrow_cooldown.finished() && keyboard.just_pressed(key_bindings.fire) {
+    arrow_cooldown.tick(TICK_DURATION);
+    if arrow_cooldown.finished() && input_buffer.input().fire {
+        input_buffer.fired();
         let (pdi_transform, facing_direction) = pdi_query.single();
-
-        let (arrow_x, arrow_y, arrow_length, arrow_width) = match facing_direction {
-            FacingDirection::Up => (
-                pdi_transform.translation().x,
-                pdi_transform.translation().y + TILE_SIZE / 2.0,
-                ARROW_LENGTH,
-                ARROW_WIDTH,
-            ),
-            FacingDirection::Down => (
-                pdi_transform.translation().x,
-                pdi_transform.translation().y - TILE_SIZE / 2.0,
-                ARROW_LENGTH,
-                ARROW_WIDTH,
-            ),
-            FacingDirection::Left => (
-                pdi_transform.translation().x - TILE_SIZE / 2.0,
-                pdi_transform.translation().y,
-                ARROW_WIDTH,
-                ARROW_LENGTH,
-            ),
-            FacingDirection::Right => (
-                pdi_transform.translation().x + TILE_SIZE / 2.0,
-                pdi_transform.translation().y,
-                ARROW_WIDTH,
-                ARROW_LENGTH,
-            ),
-        };
-
-        let shape = shapes::Rectangle {
-            extents: Vec2::new(arrow_width, arrow_length),
-            origin: RectangleOrigin::Center,
-        };
-
-        commands.spawn((
-            GeometryBuilder::build_as(
-                &shape,
-                DrawMode::Outlined {
-                    fill_mode: FillMode::color(Color::MAROON),
-                    outline_mode: StrokeMode::new(Color::BLACK, TILE_SIZE / 10.0),
-                },
-                Transform::from_translation(Vec3::new(arrow_x, arrow_y, PLAYER_LEVEL - 50.0)),
-            ),
-            Arrow::new(*facing_direction, ARROW_SPEED, Vec2::new(arrow_width, arrow_length)),
-        ));
+        fire_arrow_from(
+            &mut commands,
+            indicator_translation(player_query.single(), pdi_transform),
+            *facing_direction,
+        );
         arrow_cooldown.reset();
     }
 }
+
+/// Spawns an arrow just ahead of a direction indicator.
+pub fn fire_arrow_from(
+    commands: &mut Commands,
+    pdi_translation: Vec3,
+    facing_direction: FacingDirection,
+) {
+    let (arrow_x, arrow_y, arrow_length, arrow_width) = match facing_direction {
+        FacingDirection::Up => (
+            pdi_translation.x,
+            pdi_translation.y + TILE_SIZE / 2.0,
+            ARROW_LENGTH,
+            ARROW_WIDTH,
+        ),
+        FacingDirection::Down => (
+            pdi_translation.x,
+            pdi_translation.y - TILE_SIZE / 2.0,
+            ARROW_LENGTH,
+            ARROW_WIDTH,
+        ),
+        FacingDirection::Left => (
+            pdi_translation.x - TILE_SIZE / 2.0,
+            pdi_translation.y,
+            ARROW_WIDTH,
+            ARROW_LENGTH,
+        ),
+        FacingDirection::Right => (
+            pdi_translation.x + TILE_SIZE / 2.0,
+            pdi_translation.y,
+            ARROW_WIDTH,
+            ARROW_LENGTH,
+        ),
+    };
+
+    spawn_arrow(
+        commands,
+        Transform::from_translation(Vec3::new(arrow_x, arrow_y, PLAYER_LEVEL - 50.0)),
+        Arrow::new(
+            facing_direction,
+            ARROW_SPEED,
+            Vec2::new(arrow_width, arrow_length),
+        ),
+    );
+}
+
+pub fn spawn_arrow(commands: &mut Commands, transform: Transform, arrow: Arrow) {
+    let shape = shapes::Rectangle {
+        extents: arrow.size,
+        origin: RectangleOrigin::Center,
+    };
+
+    commands.spawn((
+        GeometryBuilder::build_as(
+            &shape,
+            DrawMode::Outlined {
+                fill_mode: FillMode::color(Color::MAROON),
+                outline_mode: StrokeMode::new(Color::BLACK, TILE_SIZE / 10.0),
+            },
+            transform,
+        ),
+        arrow,
+        Collider::aabb(arrow.size),
+        CollisionLayers::arrow(),
+        Swept::new(transform.translation),
+        SubPixel::default(),
+    ));
+}
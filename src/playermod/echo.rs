@@ -0,0 +1,278 @@
+use bevy::prelude::*;
+use core::time::Duration;
+use std::collections::VecDeque;
+
+use crate::*;
+
+pub struct EchoPlugin;
+
+impl Plugin for EchoPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EchoRecorder>()
+            .add_fixed_timestep_system(
+                TICK,
+                GAMEPLAY_STAGE,
+                record_echo
+                    .run_if_not(is_rewinding)
+                    .run_if_not(is_editing)
+                    .after("movement"),
+            )
+            .add_fixed_timestep_system(
+                TICK,
+                GAMEPLAY_STAGE,
+                replay_echoes.run_if_not(is_rewinding),
+            );
+    }
+}
+
+/// Longest stretch of input an echo can be recorded for.
+const ECHO_DURATION: f32 = 5.0;
+
+/// The player's state when an echo recording started,
+/// which the echo starts replaying from.
+struct EchoStart {
+    translation: Vec3,
+    movement_direction: MovementDirection,
+    pdi_transform: Transform,
+    facing_direction: FacingDirection,
+    rotation_pause: Timer,
+    input_buffer: InputBuffer,
+}
+
+#[derive(Default, Resource)]
+pub struct EchoRecorder {
+    start: Option<EchoStart>,
+    inputs: Vec<FrameInput>,
+}
+
+/// A ghost of the player that replays a recorded input stream through
+/// the same movement, rotation, firing, hookshot and interaction logic.
+#[derive(Component)]
+pub struct Echo {
+    body: Player,
+    sub_pixel: SubPixel,
+    inputs: VecDeque<FrameInput>,
+    rotation_pause: Timer,
+    arrow_cooldown: Timer,
+    input_buffer: InputBuffer,
+}
+
+impl Echo {
+    /// The body and sub-pixel the echo moves with, for systems that
+    /// move it outside of its replay, such as a hookshot pull.
+    pub fn mover(&mut self) -> (&mut Player, &mut SubPixel) {
+        (&mut self.body, &mut self.sub_pixel)
+    }
+}
+
+#[derive(Component)]
+pub struct EchoDirectionIndicator();
+
+/// Starts recording on the echo key, and spawns an echo replaying the
+/// recording once the key is pressed again or the recording runs out.
+fn record_echo(
+    mut commands: Commands,
+    player_query: Query<(&Transform, &Player)>,
+    pdi_query: Query<(&Transform, &FacingDirection), With<PlayerDirectionIndicator>>,
+    echo_query: Query<Entity, With<Echo>>,
+    tick_input: Res<TickInput>,
+    pause_before_move: Res<RotationBeforeMoveTimer>,
+    input_buffer: Res<InputBuffer>,
+    mut recorder: ResMut<EchoRecorder>,
+) {
+    if recorder.start.is_none() {
+        if tick_input.echo {
+            let (player_transform, player) = player_query.single();
+            let (pdi_transform, facing_direction) = pdi_query.single();
+            recorder.start = Some(EchoStart {
+                translation: player_transform.translation,
+                movement_direction: player.movement_direction,
+                pdi_transform: *pdi_transform,
+                facing_direction: *facing_direction,
+                rotation_pause: Timer::clone(&pause_before_move),
+                input_buffer: *input_buffer,
+            });
+        }
+        return;
+    }
+
+    recorder.inputs.push(**tick_input);
+
+    if tick_input.echo || recorder.inputs.len() as f32 * TICK_SECONDS >= ECHO_DURATION {
+        for echo_entity in echo_query.iter() {
+            commands.entity(echo_entity).despawn_recursive();
+        }
+        let recorder = std::mem::take(&mut *recorder);
+        if let Some(start) = recorder.start {
+            spawn_echo(&mut commands, start, recorder.inputs);
+        }
+    }
+}
+
+fn replay_echoes(
+    mut commands: Commands,
+    mut echo_query: Query<(
+        &mut Echo,
+        &mut Transform,
+        &Collider,
+        &CollisionLayers,
+        &Children,
+        Entity,
+    )>,
+    mut pdi_query: Query<
+        (&mut Transform, &mut FacingDirection),
+        (With<EchoDirectionIndicator>, Without<Echo>),
+    >,
+    collidable_query: Query<CollidableQuery, (Without<Echo>, Without<EchoDirectionIndicator>)>,
+    hookshot_query: Query<&Hookshot>,
+    current_era: Res<CurrentEra>,
+    grid: Res<CollisionGrid>,
+    mut inter_event_writer: EventWriter<InteractionEvent>,
+) {
+    for (mut echo, mut transform, echo_collider, echo_layers, children, echo_entity) in
+        echo_query.iter_mut()
+    {
+        let echo = &mut *echo;
+        let Some(input) = echo.inputs.pop_front() else {
+            commands.entity(echo_entity).despawn_recursive();
+            continue;
+        };
+        echo.input_buffer
+            .update(&input, echo.body.movement_direction);
+        let input = *echo.input_buffer.input();
+        let Some(pdi_entity) = children.iter().find(|child| pdi_query.contains(**child)) else {
+            continue;
+        };
+        let Ok((mut pdi_transform, mut facing_direction)) = pdi_query.get_mut(*pdi_entity) else {
+            continue;
+        };
+
+        if let Some(direction_held) = input.held_direction() {
+            if turn_direction_indicator(
+                echo.body.movement_direction,
+                &mut pdi_transform,
+                &mut facing_direction,
+                direction_held,
+            ) {
+                echo.rotation_pause.reset();
+            }
+            echo.rotation_pause.tick(TICK_DURATION);
+        }
+
+        let pdi_translation = indicator_translation(&transform, &pdi_transform);
+        if input.interact {
+            echo.input_buffer.interacted();
+            inter_event_writer.send(InteractionEvent::new(pdi_translation, *facing_direction));
+        }
+
+        echo.arrow_cooldown.tick(TICK_DURATION);
+        if echo.arrow_cooldown.finished() && input.fire {
+            echo.input_buffer.fired();
+            fire_arrow_from(&mut commands, pdi_translation, *facing_direction);
+            echo.arrow_cooldown.reset();
+        }
+
+        if hookshot_query
+            .iter()
+            .any(|hookshot| hookshot.owner == echo_entity)
+        {
+            continue;
+        }
+        if input.hookshot {
+            echo.input_buffer.fired_hookshot();
+            fire_hookshot_from(
+                &mut commands,
+                pdi_translation,
+                *facing_direction,
+                echo_entity,
+            );
+            continue;
+        }
+
+        if !echo.rotation_pause.finished() {
+            continue;
+        }
+        let target = transform.translation
+            + get_movement_delta(
+                &mut echo.body,
+                &transform,
+                &input,
+                TICK_SECONDS,
+                &mut echo.sub_pixel,
+            );
+        let collidables = grid.collidables_near(
+            &collidable_query,
+            target,
+            echo_collider,
+            current_era.current_era,
+        );
+        if check_collision(
+            &target,
+            echo_entity,
+            echo_collider,
+            echo_layers.mask,
+            &collidables,
+        )
+        .is_none()
+        {
+            transform.translation = target;
+        } else {
+            echo.body.movement_direction = MovementDirection::Neutral;
+        }
+    }
+}
+
+fn spawn_echo(commands: &mut Commands, start: EchoStart, inputs: Vec<FrameInput>) {
+    let shape = shapes::Circle {
+        radius: TILE_SIZE / 2.0,
+        center: Vec2::ZERO,
+    };
+
+    let pdi_shape = shapes::RegularPolygon {
+        sides: 3,
+        feature: shapes::RegularPolygonFeature::Radius(TILE_SIZE / 4.0),
+        ..Default::default()
+    };
+
+    let mut arrow_cooldown = Timer::from_seconds(ARROW_COOLDOWN, TimerMode::Once);
+    arrow_cooldown.tick(Duration::from_secs_f32(ARROW_COOLDOWN));
+
+    commands
+        .spawn((
+            GeometryBuilder::build_as(
+                &shape,
+                DrawMode::Outlined {
+                    fill_mode: FillMode::color(Color::rgba(0.0, 1.0, 1.0, 0.4)),
+                    outline_mode: StrokeMode::new(Color::BLACK, TILE_SIZE / 10.0),
+                },
+                Transform::from_translation(start.translation - Vec3::Z),
+            ),
+            Echo {
+                body: Player {
+                    speed: PLAYER_SPEED,
+                    movement_direction: start.movement_direction,
+                },
+                sub_pixel: SubPixel::default(),
+                inputs: inputs.into(),
+                rotation_pause: start.rotation_pause,
+                arrow_cooldown,
+                input_buffer: start.input_buffer,
+            },
+            Collider::circle(TILE_SIZE / 2.0),
+            CollisionLayers::player(),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                GeometryBuilder::build_as(
+                    &pdi_shape,
+                    DrawMode::Outlined {
+                        fill_mode: FillMode::color(Color::rgba(0.5, 0.5, 0.0, 0.4)),
+                        outline_mode: StrokeMode::new(Color::BLACK, TILE_SIZE / 10.0),
+                    },
+                    start.pdi_transform,
+                ),
+                start.facing_direction,
+                EchoDirectionIndicator(),
+            ));
+        });
+}
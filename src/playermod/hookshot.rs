@@ -1,47 +1,124 @@
+use bevy::ecs::system::EntityCommands;
 use bevy::prelude::*;
+use bevy::utils::HashSet;
 use std::f32::consts::PI;
 
+use crate::events::CollisionEvent;
+use crate::levelmod::level::{tile_position, Level};
 use crate::*;
 
 pub struct HookshotPlugin;
 
 impl Plugin for HookshotPlugin {
     fn build(&self, app: &mut App) {
-        app.add_system(fire_hookshot.label("firehookshot"))
-            .add_system(hookshot_move.label("hookshotmove"))
-            .add_system(manage_hookshot_collisions.after("hookshotmove"))
-            .add_system(despawn_hookshot_out_of_range.after("hookshotmove"))
-            .insert_resource(HookshotFiring(false));
+        app.add_fixed_timestep_system(
+            TICK,
+            GAMEPLAY_STAGE,
+            fire_hookshot
+                .run_if_not(is_rewinding)
+                .run_if_not(is_editing)
+                .label("firehookshot"),
+        )
+        .add_fixed_timestep_system(
+            TICK,
+            GAMEPLAY_STAGE,
+            hookshot_move.run_if_not(is_rewinding).label("hookshotmove"),
+        )
+        .add_fixed_timestep_system(
+            TICK,
+            GAMEPLAY_STAGE,
+            pull_owner_to_hookshot
+                .run_if_not(is_rewinding)
+                .before("movement"),
+        )
+        .add_fixed_timestep_system(
+            TICK,
+            GAMEPLAY_STAGE,
+            drag_target_to_owner.run_if_not(is_rewinding),
+        )
+        .add_fixed_timestep_system(
+            TICK,
+            COLLISION_STAGE,
+            manage_hookshot_collisions.after("collisiondetection"),
+        )
+        .add_fixed_timestep_system(
+            TICK,
+            GAMEPLAY_STAGE,
+            retract_hookshot_out_of_range
+                .run_if_not(is_rewinding)
+                .label("hookshotrange")
+                .after("hookshotmove"),
+        )
+        .add_fixed_timestep_system(
+            TICK,
+            GAMEPLAY_STAGE,
+            retract_hookshot
+                .run_if_not(is_rewinding)
+                .after("hookshotrange"),
+        )
+        .insert_resource(HookshotFiring(false));
     }
 }
 
+/// Whether the player has a hookshot out. Echoes have
+/// one out for as long as a hookshot they own exists.
 #[derive(Default, Resource)]
 pub struct HookshotFiring(pub bool);
 
-#[derive(Component)]
-pub struct HookshotHitBlock{
-    pub block_translation: Vec3,
+pub fn is_hookshot_firing(hookshot_firing: Res<HookshotFiring>) -> bool {
+    hookshot_firing.0
 }
 
-#[derive(Component)]
-pub struct Hookshotable();
+/// Which end of a latched hookshot gets pulled in.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PullDirection {
+    /// Whoever fired it is pulled over to the target.
+    TowardTarget,
+    /// The target is dragged back to whoever fired it.
+    TowardOwner,
+}
 
-#[derive(Component)]
+impl From<Weight> for PullDirection {
+    fn from(weight: Weight) -> Self {
+        match weight {
+            Weight::Heavy => PullDirection::TowardTarget,
+            Weight::Light => PullDirection::TowardOwner,
+        }
+    }
+}
+
+/// The block a latched hookshot is attached to.
+#[derive(Clone, Component, Copy)]
+pub struct HookshotHitBlock {
+    pub block: Entity,
+    pub block_translation: Vec3,
+    pub pull: PullDirection,
+}
+
+#[derive(Clone, Component, Copy)]
 pub struct Hookshot {
+    /// The player or echo that fired it.
+    pub owner: Entity,
     pub facing_direction: FacingDirection,
     pub speed: f32,
     pub size: f32,
+    /// Whether it is on its way back after missing.
+    pub is_retracting: bool,
 }
 
 const HOOKSHOT_SPEED: f32 = 7.5;
 const HOOKSHOT_SIZE: f32 = TILE_SIZE / 2.0;
+/// How far from its owner the hookshot reaches before it comes back.
+const HOOKSHOT_RANGE: f32 = TILE_SIZE * 3.4;
 
 impl Hookshot {
-    pub fn new(facing_direction: FacingDirection, speed: f32, size: f32) -> Self {
+    pub fn new(owner: Entity, facing_direction: FacingDirection, speed: f32, size: f32) -> Self {
         Self {
+            owner,
             facing_direction,
             speed,
             size,
+            is_retracting: false,
         }
     }
 
@@ -54,92 +131,313 @@ impl Hookshot {
     }
 }
 
-fn despawn_hookshot_out_of_range(
+/// Despawns a hookshot, letting whoever fired it fire again.
+fn put_away(
+    commands: &mut Commands,
+    hookshot_entity: Entity,
+    hookshot: &Hookshot,
+    player: Option<Entity>,
+    hookshot_firing: &mut HookshotFiring,
+) {
+    commands.entity(hookshot_entity).despawn();
+    if player == Some(hookshot.owner) {
+        hookshot_firing.0 = false;
+    }
+}
+
+fn retract_hookshot_out_of_range(
+    owner_query: Query<&Transform, Without<Hookshot>>,
+    mut hookshot_query: Query<(&Transform, &mut Hookshot), Without<HookshotHitBlock>>,
+) {
+    for (hookshot_transform, mut hookshot) in hookshot_query.iter_mut() {
+        let Ok(owner_transform) = owner_query.get(hookshot.owner) else {
+            hookshot.is_retracting = true;
+            continue;
+        };
+        if (owner_transform.translation.x - hookshot_transform.translation.x).abs() > HOOKSHOT_RANGE
+            || (owner_transform.translation.y - hookshot_transform.translation.y).abs()
+                > HOOKSHOT_RANGE
+        {
+            hookshot.is_retracting = true;
+        }
+    }
+}
+
+/// Brings a retracting hookshot back to its owner's direction
+/// indicator, which unlocks firing once it gets there.
+fn retract_hookshot(
+    mut commands: Commands,
+    owner_query: Query<(&Transform, &Children), Without<Hookshot>>,
+    indicator_query: Query<
+        &Transform,
+        (
+            Or<(With<PlayerDirectionIndicator>, With<EchoDirectionIndicator>)>,
+            Without<Hookshot>,
+        ),
+    >,
+    player_query: Query<Entity, With<Player>>,
+    mut hookshot_query: Query<(Entity, &mut Transform, &mut Swept, &mut SubPixel, &Hookshot)>,
+    mut hookshot_firing: ResMut<HookshotFiring>,
+) {
+    let player = player_query.get_single().ok();
+    for (hookshot_entity, mut transform, mut swept, mut sub_pixel, hookshot) in
+        hookshot_query.iter_mut()
+    {
+        if !hookshot.is_retracting {
+            continue;
+        }
+        let indicator =
+            owner_query
+                .get(hookshot.owner)
+                .ok()
+                .and_then(|(owner_transform, children)| {
+                    children.iter().find_map(|child| {
+                        let pdi_transform = indicator_query.get(*child).ok()?;
+                        Some(indicator_translation(owner_transform, pdi_transform))
+                    })
+                });
+        let Some(pdi_translation) = indicator else {
+            put_away(
+                &mut commands,
+                hookshot_entity,
+                hookshot,
+                player,
+                &mut hookshot_firing,
+            );
+            continue;
+        };
+        swept.previous_translation = transform.translation;
+        let to_indicator = (pdi_translation - transform.translation).truncate();
+        let step = sub_pixel.step(get_manual_movement_speed(hookshot.speed(), TICK_SECONDS));
+        if to_indicator.length() <= step {
+            put_away(
+                &mut commands,
+                hookshot_entity,
+                hookshot,
+                player,
+                &mut hookshot_firing,
+            );
+        } else {
+            transform.translation += (to_indicator.normalize() * step).extend(0.0);
+        }
+    }
+}
+
+/// Pulls whoever fired a latched hookshot along it, over anything in
+/// the way, to the tile in front of what it latched onto. The hookshot
+/// is put away once they get there.
+fn pull_owner_to_hookshot(
     mut commands: Commands,
-    mut player_query: Query<&Transform, With<Player>>,
+    mut owner_query: Query<
+        (
+            &mut Transform,
+            Option<(&mut Player, &mut SubPixel)>,
+            Option<&mut Echo>,
+        ),
+        Without<Hookshot>,
+    >,
+    player_query: Query<Entity, With<Player>>,
+    hookshot_query: Query<(Entity, &Hookshot, &HookshotHitBlock)>,
     mut hookshot_firing: ResMut<HookshotFiring>,
-    hookshot_query: Query<(&Transform, Entity), (With<Hookshot>, Without<Player>)>,
 ) {
-    for player_transform in player_query.iter_mut() {
-        for (hookshot_transform, hookshot_entity) in hookshot_query.iter() {
-            if (player_transform.translation.x - hookshot_transform.translation.x).abs()
-                > TILE_SIZE * 3.4
-                || (player_transform.translation.y - hookshot_transform.translation.y).abs()
-                    > TILE_SIZE * 3.4
-            {
-                commands.entity(hookshot_entity).despawn();
-                hookshot_firing.0 = false;
-            }
+    let player = player_query.get_single().ok();
+    for (hookshot_entity, hookshot, hit_block) in hookshot_query.iter() {
+        if hit_block.pull != PullDirection::TowardTarget {
+            continue;
+        }
+        let Ok((mut transform, player_mover, echo)) = owner_query.get_mut(hookshot.owner) else {
+            put_away(
+                &mut commands,
+                hookshot_entity,
+                hookshot,
+                player,
+                &mut hookshot_firing,
+            );
+            continue;
+        };
+        let (mover, sub_pixel) = match (player_mover, echo) {
+            (Some((mover, sub_pixel)), _) => (mover.into_inner(), sub_pixel.into_inner()),
+            (None, Some(echo)) => echo.into_inner().mover(),
+            (None, None) => continue,
+        };
+        let offset = hookshot.facing_direction().tile_offset();
+        let direction = offset / TILE_SIZE;
+        let distance_left =
+            (hit_block.block_translation - offset - transform.translation).dot(direction);
+        let step = sub_pixel.step(get_manual_movement_speed(hookshot.speed(), TICK_SECONDS));
+        if step < distance_left {
+            transform.translation += direction * step;
+            mover.movement_direction = hookshot.facing_direction().movement_direction();
+        } else {
+            transform.translation += direction * distance_left;
+            mover.movement_direction = MovementDirection::Neutral;
+            sub_pixel.clear();
+            put_away(
+                &mut commands,
+                hookshot_entity,
+                hookshot,
+                player,
+                &mut hookshot_firing,
+            );
+        }
+    }
+}
+
+/// Drags a light target along a latched hookshot, over anything in the
+/// way, to the tile in front of whoever fired it. The hookshot is put
+/// away once the target gets there, or if either of them is gone.
+fn drag_target_to_owner(
+    mut commands: Commands,
+    player_query: Query<Entity, With<Player>>,
+    mut hookshot_query: Query<(
+        Entity,
+        &mut Transform,
+        &mut SubPixel,
+        &Hookshot,
+        &HookshotHitBlock,
+    )>,
+    mut target_query: Query<&mut Transform, Without<Hookshot>>,
+    mut hookshot_firing: ResMut<HookshotFiring>,
+) {
+    let player = player_query.get_single().ok();
+    for (hookshot_entity, mut hookshot_transform, mut sub_pixel, hookshot, hit_block) in
+        hookshot_query.iter_mut()
+    {
+        if hit_block.pull != PullDirection::TowardOwner {
+            continue;
+        }
+        let owner_translation = target_query
+            .get(hookshot.owner)
+            .map(|owner_transform| owner_transform.translation);
+        let (Ok(owner_translation), Ok(mut target_transform)) =
+            (owner_translation, target_query.get_mut(hit_block.block))
+        else {
+            put_away(
+                &mut commands,
+                hookshot_entity,
+                hookshot,
+                player,
+                &mut hookshot_firing,
+            );
+            continue;
+        };
+        let offset = hookshot.facing_direction().tile_offset();
+        let direction = offset / TILE_SIZE;
+        let distance_left =
+            (target_transform.translation - owner_translation - offset).dot(direction);
+        let step = sub_pixel.step(get_manual_movement_speed(hookshot.speed(), TICK_SECONDS));
+        let delta = -direction * step.min(distance_left);
+        target_transform.translation += delta;
+        hookshot_transform.translation += delta;
+        if step >= distance_left {
+            put_away(
+                &mut commands,
+                hookshot_entity,
+                hookshot,
+                player,
+                &mut hookshot_firing,
+            );
         }
     }
 }
 
 fn fire_hookshot(
     mut commands: Commands,
-    pdi_query: Query<(&mut GlobalTransform, &mut FacingDirection), With<PlayerDirectionIndicator>>,
+    player_query: Query<(&Transform, Entity), With<Player>>,
+    pdi_query: Query<(&Transform, &FacingDirection), With<PlayerDirectionIndicator>>,
     mut hookshot_firing: ResMut<HookshotFiring>,
-    keyboard: Res<Input<KeyCode>>,
-    key_bindings: Res<KeyBindings>,
+    mut input_buffer: ResMut<InputBuffer>,
 ) {
-    if keyboard.just_pressed(key_bindings.hookshot) && !hookshot_firing.0 {
+    if input_buffer.input().hookshot && !hookshot_firing.0 {
         hookshot_firing.0 = true;
+        input_buffer.fired_hookshot();
+        let (player_transform, player) = player_query.single();
         let (pdi_transform, facing_direction) = pdi_query.single();
+        fire_hookshot_from(
+            &mut commands,
+            indicator_translation(player_transform, pdi_transform),
+            *facing_direction,
+            player,
+        );
+    }
+}
 
-        let (hookshot_x, hookshot_y, roation_angle) = match facing_direction {
-            FacingDirection::Up => (
-                pdi_transform.translation().x,
-                pdi_transform.translation().y + TILE_SIZE / 2.0,
-                0.0,
-            ),
-            FacingDirection::Down => (
-                pdi_transform.translation().x,
-                pdi_transform.translation().y - TILE_SIZE / 2.0,
-                PI,
-            ),
-            FacingDirection::Left => (
-                pdi_transform.translation().x - TILE_SIZE / 2.0,
-                pdi_transform.translation().y,
-                PI / 2.0,
-            ),
-            FacingDirection::Right => (
-                pdi_transform.translation().x + TILE_SIZE / 2.0,
-                pdi_transform.translation().y,
-                3.0 * PI / 2.0,
-            ),
-        };
+/// Spawns a hookshot just ahead of a direction indicator.
+pub fn fire_hookshot_from(
+    commands: &mut Commands,
+    pdi_translation: Vec3,
+    facing_direction: FacingDirection,
+    owner: Entity,
+) {
+    let (hookshot_x, hookshot_y, roation_angle) = match facing_direction {
+        FacingDirection::Up => (pdi_translation.x, pdi_translation.y + TILE_SIZE / 2.0, 0.0),
+        FacingDirection::Down => (pdi_translation.x, pdi_translation.y - TILE_SIZE / 2.0, PI),
+        FacingDirection::Left => (
+            pdi_translation.x - TILE_SIZE / 2.0,
+            pdi_translation.y,
+            PI / 2.0,
+        ),
+        FacingDirection::Right => (
+            pdi_translation.x + TILE_SIZE / 2.0,
+            pdi_translation.y,
+            3.0 * PI / 2.0,
+        ),
+    };
 
-        let pdi_shape = shapes::RegularPolygon {
-            sides: 3,
-            feature: shapes::RegularPolygonFeature::Radius(TILE_SIZE / 4.0),
+    spawn_hookshot(
+        commands,
+        Transform {
+            translation: Vec3::new(hookshot_x, hookshot_y, PLAYER_LEVEL - 25.0),
+            rotation: Quat::from_rotation_z(roation_angle),
             ..Default::default()
-        };
+        },
+        Hookshot::new(owner, facing_direction, HOOKSHOT_SPEED, HOOKSHOT_SIZE),
+    );
+}
 
-        commands.spawn((
-            GeometryBuilder::build_as(
-                &pdi_shape,
-                DrawMode::Outlined {
-                    fill_mode: FillMode::color(Color::GREEN),
-                    outline_mode: StrokeMode::new(Color::BLACK, TILE_SIZE / 10.0),
-                },
-                Transform {
-                    translation: Vec3::new(hookshot_x, hookshot_y, PLAYER_LEVEL - 25.0),
-                    rotation: Quat::from_rotation_z(roation_angle),
-                    ..Default::default()
-                },
-            ),
-            Hookshot::new(*facing_direction, HOOKSHOT_SPEED, HOOKSHOT_SIZE),
-        ));
-    }
+pub fn spawn_hookshot<'w, 's, 'a>(
+    commands: &'a mut Commands<'w, 's>,
+    transform: Transform,
+    hookshot: Hookshot,
+) -> EntityCommands<'w, 's, 'a> {
+    let pdi_shape = shapes::RegularPolygon {
+        sides: 3,
+        feature: shapes::RegularPolygonFeature::Radius(TILE_SIZE / 4.0),
+        ..Default::default()
+    };
+
+    commands.spawn((
+        GeometryBuilder::build_as(
+            &pdi_shape,
+            DrawMode::Outlined {
+                fill_mode: FillMode::color(Color::GREEN),
+                outline_mode: StrokeMode::new(Color::BLACK, TILE_SIZE / 10.0),
+            },
+            transform,
+        ),
+        hookshot,
+        Collider::aabb(Vec2::new(hookshot.size, hookshot.size)),
+        CollisionLayers::hookshot(),
+        Swept::new(transform.translation),
+        SubPixel::default(),
+    ))
 }
 
 fn hookshot_move(
-    mut hookshot_query: Query<(&mut Transform, &Hookshot), Without<HookshotHitBlock>>,
-    time: Res<Time>,
+    mut hookshot_query: Query<
+        (&mut Transform, &mut Swept, &mut SubPixel, &Hookshot),
+        Without<HookshotHitBlock>,
+    >,
 ) {
-    for (mut transform, hookshot) in hookshot_query.iter_mut() {
+    for (mut transform, mut swept, mut sub_pixel, hookshot) in hookshot_query.iter_mut() {
+        if hookshot.is_retracting {
+            continue;
+        }
+        swept.previous_translation = transform.translation;
         let mut delta_x: f32 = 0.0;
         let mut delta_y: f32 = 0.0;
-        let movement_speed = get_manual_movement_speed(hookshot.speed(), time.delta_seconds());
+        let movement_speed =
+            sub_pixel.step(get_manual_movement_speed(hookshot.speed(), TICK_SECONDS));
         match hookshot.facing_direction() {
             FacingDirection::Up => delta_y += movement_speed,
             FacingDirection::Down => delta_y -= movement_speed,
@@ -151,26 +449,51 @@ fn hookshot_move(
     }
 }
 
+/// Latches the hookshot onto the first target it hits, or sends it back
+/// from anything else that stops it, moving it back to where it first
+/// touched what it hit. The target's weight decides what gets pulled,
+/// and a heavy target in front of anything but open floor sends it back.
 fn manage_hookshot_collisions(
     mut commands: Commands,
-    mut hookshot_query: Query<(&Transform, Entity, &Hookshot), With<Hookshot>>,
-    collidable_query: Query<(&Transform, Entity), With<Hookshotable>>,
+    mut hookshot_query: Query<(Entity, &mut Transform, &mut Hookshot), Without<HookshotHitBlock>>,
+    target_query: Query<(&Transform, &CollisionLayers, Option<&Weight>), Without<Hookshot>>,
+    levels: Res<Assets<Level>>,
+    current_level: Res<CurrentLevel>,
+    current_era: Res<CurrentEra>,
+    mut event: EventReader<CollisionEvent>,
 ) {
-    let collidables: Vec<(Vec3, u32)> = collidable_query
-        .iter()
-        .map(|(transform, entity)| (transform.translation, entity.index()))
-        .collect();
-    for (hookshot_transform, hookshot_entity, hookshot) in hookshot_query.iter_mut() {
-        if let Some(collidable_translation) = check_collision(
-            &hookshot_transform.translation,
-            &hookshot_entity.index(),
-            &collidables,
-            Vec2::new(hookshot.size, hookshot.size),
-        ) {
-            if let Some(mut hookshot_entity) = commands.get_entity(hookshot_entity) {
-                hookshot_entity.insert(HookshotHitBlock{block_translation: collidable_translation});
-            }
-            return;
+    let level = levels.get(&current_level.handle);
+    let mut stopped_hookshots = HashSet::new();
+    for collision_event in event.iter() {
+        let Ok((hookshot_entity, mut hookshot_transform, mut hookshot)) =
+            hookshot_query.get_mut(collision_event.entity())
+        else {
+            continue;
+        };
+        let Ok((target_transform, target_layers, target_weight)) =
+            target_query.get(collision_event.other())
+        else {
+            continue;
+        };
+        if hookshot.is_retracting || !stopped_hookshots.insert(hookshot_entity) {
+            continue;
+        }
+        hookshot_transform.translation = collision_event.contact();
+        let pull = target_weight.copied().unwrap_or(Weight::Heavy).into();
+        // Whoever is pulled over lands in front of the target,
+        // so it only latches if they can stand there.
+        let landing = target_transform.translation - hookshot.facing_direction().tile_offset();
+        let can_land = pull == PullDirection::TowardOwner
+            || level
+                .is_none_or(|level| level.is_open(current_era.current_era, tile_position(landing)));
+        if target_layers.is_in(CollisionLayers::HOOKSHOT_TARGET) && can_land {
+            commands.entity(hookshot_entity).insert(HookshotHitBlock {
+                block: collision_event.other(),
+                block_translation: target_transform.translation,
+                pull,
+            });
+        } else {
+            hookshot.is_retracting = true;
         }
     }
 }
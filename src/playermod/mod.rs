@@ -3,7 +3,9 @@ use bevy::app::PluginGroupBuilder;
 
 pub mod player;
 pub mod arrows;
+pub mod echo;
 pub mod hookshot;
+pub mod rewind;
 
 pub struct PlayerModPluginGroup;
 
@@ -13,5 +15,7 @@ impl PluginGroup for PlayerModPluginGroup {
             .add(PlayerPlugin)
             .add(ArrowsPlugin)
             .add(HookshotPlugin)
+            .add(RewindPlugin)
+            .add(EchoPlugin)
     }
 }
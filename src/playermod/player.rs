@@ -13,17 +13,45 @@ impl Plugin for PlayerPlugin {
         app.add_startup_system(spawn_player.label("playerspawn"))
             .add_startup_system(rotation_pause_timer_init)
             .init_resource::<RotationBeforeMoveTimer>()
-            .add_system(player_movement.label("movement").after("rotation"))
-            .add_system(camera_follow.after("movement"))
-            .add_system(rotate_player_direction_indicator.label("rotation"))
-            .add_system(interact);
+            .init_resource::<InputBuffer>()
+            .add_fixed_timestep_system(
+                TICK,
+                INPUT_STAGE,
+                buffer_player_input
+                    .run_if_not(is_rewinding)
+                    .run_if_not(is_editing)
+                    .after("inputplayback"),
+            )
+            .add_fixed_timestep_system(
+                TICK,
+                GAMEPLAY_STAGE,
+                player_movement
+                    .run_if_not(is_rewinding)
+                    .run_if_not(is_editing)
+                    .label("movement")
+                    .after("rotation"),
+            )
+            .add_fixed_timestep_system(TICK, GAMEPLAY_STAGE, camera_follow.after("movement"))
+            .add_fixed_timestep_system(
+                TICK,
+                GAMEPLAY_STAGE,
+                rotate_player_direction_indicator
+                    .run_if_not(is_rewinding)
+                    .run_if_not(is_editing)
+                    .label("rotation"),
+            )
+            .add_fixed_timestep_system(
+                TICK,
+                GAMEPLAY_STAGE,
+                interact.run_if_not(is_rewinding).run_if_not(is_editing),
+            );
     }
 }
 
 #[derive(PartialEq, Debug)]
 struct Rotation(f32);
 
-const PLAYER_SPEED: f32 = 4.0;
+pub const PLAYER_SPEED: f32 = 4.0;
 const PI_OVER_TWO: Rotation = Rotation(PI / 2.0);
 const THREE_PI_OVER_TWO: Rotation = Rotation(3.0 * PI / 2.0);
 const ZERO_PI: Rotation = Rotation(0.0);
@@ -31,7 +59,7 @@ const ZERO_PI: Rotation = Rotation(0.0);
 const PAUSE_BEFORE_MOVE: f32 = 0.15;
 
 #[derive(Resource, Deref, DerefMut)]
-struct RotationBeforeMoveTimer(Timer);
+pub struct RotationBeforeMoveTimer(Timer);
 
 impl Default for RotationBeforeMoveTimer {
     fn default() -> Self {
@@ -56,25 +84,124 @@ pub enum FacingDirection {
     Right = 3,
 }
 
+impl FacingDirection {
+    /// A tile's width in this direction.
+    pub fn tile_offset(self) -> Vec3 {
+        match self {
+            FacingDirection::Up => Vec3::new(0.0, TILE_SIZE, 0.0),
+            FacingDirection::Down => Vec3::new(0.0, -TILE_SIZE, 0.0),
+            FacingDirection::Left => Vec3::new(-TILE_SIZE, 0.0, 0.0),
+            FacingDirection::Right => Vec3::new(TILE_SIZE, 0.0, 0.0),
+        }
+    }
+
+    /// The way a mover heads when walking in this direction.
+    pub fn movement_direction(self) -> MovementDirection {
+        match self {
+            FacingDirection::Up => MovementDirection::Up,
+            FacingDirection::Down => MovementDirection::Down,
+            FacingDirection::Left => MovementDirection::Left,
+            FacingDirection::Right => MovementDirection::Right,
+        }
+    }
+}
+
 #[derive(Component)]
 pub struct PlayerDirectionIndicator();
 
 #[derive(Component)]
 pub struct Player {
-    speed: f32,
-    movement_direction: MovementDirection,
+    pub speed: f32,
+    pub movement_direction: MovementDirection,
+}
+
+/// The part of a pixel a mover has travelled but not yet moved,
+/// carried into its next move so that rounding never slows it down.
+#[derive(Clone, Component, Copy, Debug, Default, PartialEq)]
+pub struct SubPixel(f32);
+
+impl SubPixel {
+    /// Whole pixels to move for `distance`, keeping the rest for later.
+    pub fn step(&mut self, distance: f32) -> f32 {
+        let total = distance + self.0;
+        let whole = total.trunc();
+        self.0 = total - whole;
+        whole
+    }
+
+    pub fn clear(&mut self) {
+        self.0 = 0.0;
+    }
+}
+
+#[cfg(test)]
+mod test_sub_pixel {
+    use super::*;
+
+    #[test]
+    fn test_step_carries_fraction() {
+        let mut sub_pixel = SubPixel::default();
+        let steps: Vec<f32> = (0..3).map(|_| sub_pixel.step(0.4)).collect();
+        assert_eq!(steps, vec![0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn test_step_total_matches_distance() {
+        let mut sub_pixel = SubPixel::default();
+        let distance = get_manual_movement_speed(PLAYER_SPEED, TICK_SECONDS);
+        let total: f32 = (0..TICKS_PER_SECOND)
+            .map(|_| sub_pixel.step(distance))
+            .sum();
+        assert_eq!(total, PLAYER_SPEED * TILE_SIZE);
+    }
+}
+
+/// Where a direction indicator is in the world. Worked out from its parent
+/// rather than its `GlobalTransform`, which is only updated once a frame
+/// however many ticks run in it.
+pub fn indicator_translation(parent_transform: &Transform, pdi_transform: &Transform) -> Vec3 {
+    parent_transform.mul_transform(*pdi_transform).translation
+}
+
+#[cfg(test)]
+mod test_indicator_translation {
+    use super::*;
+
+    #[test]
+    fn test_indicator_translation_follows_parent() {
+        let parent_transform = Transform::from_translation(Vec3::new(TILE_SIZE, 0.0, PLAYER_LEVEL));
+        let pdi_transform = Transform::from_translation(Vec3::new(0.0, TILE_SIZE / 4.0, 1.0));
+        assert_eq!(
+            indicator_translation(&parent_transform, &pdi_transform),
+            Vec3::new(TILE_SIZE, TILE_SIZE / 4.0, PLAYER_LEVEL + 1.0)
+        );
+    }
+}
+
+fn buffer_player_input(
+    player_query: Query<&Player>,
+    tick_input: Res<TickInput>,
+    mut input_buffer: ResMut<InputBuffer>,
+) {
+    let movement_direction = player_query
+        .get_single()
+        .map_or(MovementDirection::Neutral, |player| {
+            player.movement_direction
+        });
+    input_buffer.update(&tick_input, movement_direction);
 }
 
 fn interact(
-    player_query: Query<(&GlobalTransform, &FacingDirection), With<PlayerDirectionIndicator>>,
+    player_query: Query<&Transform, With<Player>>,
+    pdi_query: Query<(&Transform, &FacingDirection), With<PlayerDirectionIndicator>>,
     mut inter_event_writer: EventWriter<InteractionEvent>,
-    keyboard: Res<Input<KeyCode>>,
-    key_bindings: Res<KeyBindings>,
+    mut input_buffer: ResMut<InputBuffer>,
 ) {
-    if keyboard.just_pressed(key_bindings.interact) {
-        let (pdi_transform, facing_direction) = player_query.single();
+    if input_buffer.input().interact {
+        input_buffer.interacted();
+        let (pdi_transform, facing_direction) = pdi_query.single();
         inter_event_writer.send(InteractionEvent::new(
-            pdi_transform.translation(),
+            indicator_translation(player_query.single(), pdi_transform),
             *facing_direction,
         ));
     }
@@ -85,7 +212,7 @@ fn rotation_pause_timer_init(mut timer: ResMut<RotationBeforeMoveTimer>) {
 }
 
 pub fn get_manual_movement_speed(player_speed: f32, delta_seconds: f32) -> f32 {
-    (player_speed * TILE_SIZE * delta_seconds) as i32 as f32
+    player_speed * TILE_SIZE * delta_seconds
 }
 
 #[cfg(test)]
@@ -104,40 +231,43 @@ fn rotate_player_direction_indicator(
         (With<PlayerDirectionIndicator>, Without<Player>),
     >,
     player_query: Query<&Player, With<Player>>,
-    keyboard: Res<Input<KeyCode>>,
-    key_bindings: Res<KeyBindings>,
+    input_buffer: Res<InputBuffer>,
     mut pause_before_move: ResMut<RotationBeforeMoveTimer>,
-    time: Res<Time>,
 ) {
     let player = player_query.single();
     let (mut pdi_transform, mut facing_direction) = pdi_query.single_mut();
 
-    let key_pressed: KeyCode;
-
-    if keyboard.pressed(key_bindings.up) {
-        key_pressed = key_bindings.up;
-    } else if keyboard.pressed(key_bindings.down) {
-        key_pressed = key_bindings.down;
-    } else if keyboard.pressed(key_bindings.left) {
-        key_pressed = key_bindings.left;
-    } else if keyboard.pressed(key_bindings.right) {
-        key_pressed = key_bindings.right;
-    } else {
+    let Some(direction_held) = input_buffer.input().held_direction() else {
         return;
+    };
+
+    if turn_direction_indicator(
+        player.movement_direction,
+        &mut pdi_transform,
+        &mut facing_direction,
+        direction_held,
+    ) {
+        pause_before_move.0.reset();
     }
 
+    pause_before_move.tick(TICK_DURATION);
+}
+
+/// Turns a direction indicator towards the held direction,
+/// returning whether its facing direction changed.
+pub fn turn_direction_indicator(
+    movement_direction: MovementDirection,
+    pdi_transform: &mut Transform,
+    facing_direction: &mut FacingDirection,
+    direction_held: FacingDirection,
+) -> bool {
     let (rotation_angle, new_facing_direction) = get_new_angle_and_facing_direction_for_pdi(
-        player.movement_direction,
+        movement_direction,
         *facing_direction,
-        key_pressed,
-        key_bindings.into_inner(),
+        direction_held,
     );
 
-    if *facing_direction != new_facing_direction {
-        pause_before_move.0.reset();
-    }
-
-    pause_before_move.tick(time.delta());
+    let is_turning = *facing_direction != new_facing_direction;
 
     *facing_direction = new_facing_direction;
 
@@ -145,13 +275,13 @@ fn rotate_player_direction_indicator(
         Vec3::new(0.0, 0.0, 0.0),
         Quat::from_rotation_z(rotation_angle.0),
     );
+    is_turning
 }
 
 fn get_new_angle_and_facing_direction_for_pdi(
     player_movement_direction: MovementDirection,
     pdi_facing_direction: FacingDirection,
-    key_pressed: KeyCode,
-    key_bindings: &KeyBindings,
+    direction_held: FacingDirection,
 ) -> (Rotation, FacingDirection) {
     let new_rotation_angle: Rotation;
     let new_facing_direction: FacingDirection;
@@ -194,42 +324,33 @@ fn get_new_angle_and_facing_direction_for_pdi(
             MovementDirection::Neutral => pdi_facing_direction,
         };
     } else if player_movement_direction == MovementDirection::Neutral {
-        if key_pressed == key_bindings.up {
-            new_rotation_angle = match pdi_facing_direction {
+        new_rotation_angle = match direction_held {
+            FacingDirection::Up => match pdi_facing_direction {
                 FacingDirection::Up => ZERO_PI,
                 FacingDirection::Down => Rotation(PI),
                 FacingDirection::Left => THREE_PI_OVER_TWO,
                 FacingDirection::Right => PI_OVER_TWO,
-            };
-            new_facing_direction = FacingDirection::Up;
-        } else if key_pressed == key_bindings.down {
-            new_rotation_angle = match pdi_facing_direction {
+            },
+            FacingDirection::Down => match pdi_facing_direction {
                 FacingDirection::Up => Rotation(PI),
                 FacingDirection::Down => ZERO_PI,
                 FacingDirection::Left => PI_OVER_TWO,
                 FacingDirection::Right => THREE_PI_OVER_TWO,
-            };
-            new_facing_direction = FacingDirection::Down;
-        } else if key_pressed == key_bindings.left {
-            new_rotation_angle = match pdi_facing_direction {
+            },
+            FacingDirection::Left => match pdi_facing_direction {
                 FacingDirection::Up => PI_OVER_TWO,
                 FacingDirection::Down => THREE_PI_OVER_TWO,
                 FacingDirection::Left => ZERO_PI,
                 FacingDirection::Right => Rotation(PI),
-            };
-            new_facing_direction = FacingDirection::Left;
-        } else if key_pressed == key_bindings.right {
-            new_rotation_angle = match pdi_facing_direction {
+            },
+            FacingDirection::Right => match pdi_facing_direction {
                 FacingDirection::Up => THREE_PI_OVER_TWO,
                 FacingDirection::Down => PI_OVER_TWO,
                 FacingDirection::Left => Rotation(PI),
                 FacingDirection::Right => ZERO_PI,
-            };
-            new_facing_direction = FacingDirection::Right;
-        } else {
-            new_rotation_angle = ZERO_PI;
-            new_facing_direction = pdi_facing_direction;
-        }
+            },
+        };
+        new_facing_direction = direction_held;
     } else {
         new_rotation_angle = ZERO_PI;
         new_facing_direction = pdi_facing_direction;
@@ -245,13 +366,11 @@ mod test_get_new_angle_and_facing_direction_for_pdi {
     fn test_player_moving_neutral_up_pressed_pdi_facing_up() {
         let player_movement_direction = MovementDirection::Neutral;
         let pdi_facing_direction = FacingDirection::Up;
-        let key_bindings = KeyBindings::default();
-        let key_pressed = key_bindings.up;
+        let direction_held = FacingDirection::Up;
         let (new_rotation_angle, new_facing_direction) = get_new_angle_and_facing_direction_for_pdi(
             player_movement_direction,
             pdi_facing_direction,
-            key_pressed,
-            &key_bindings,
+            direction_held,
         );
         assert_eq!(new_rotation_angle, ZERO_PI);
         assert_eq!(new_facing_direction, FacingDirection::Up);
@@ -261,13 +380,11 @@ mod test_get_new_angle_and_facing_direction_for_pdi {
     fn test_player_moving_neutral_up_pressed_pdi_facing_down() {
         let player_movement_direction = MovementDirection::Neutral;
         let pdi_facing_direction = FacingDirection::Down;
-        let key_bindings = KeyBindings::default();
-        let key_pressed = key_bindings.up;
+        let direction_held = FacingDirection::Up;
         let (new_rotation_angle, new_facing_direction) = get_new_angle_and_facing_direction_for_pdi(
             player_movement_direction,
             pdi_facing_direction,
-            key_pressed,
-            &key_bindings,
+            direction_held,
         );
         assert_eq!(new_rotation_angle, Rotation(PI));
         assert_eq!(new_facing_direction, FacingDirection::Up);
@@ -277,13 +394,11 @@ mod test_get_new_angle_and_facing_direction_for_pdi {
     fn test_player_moving_left_left_pressed_pdi_facing_down() {
         let player_movement_direction = MovementDirection::Left;
         let pdi_facing_direction = FacingDirection::Down;
-        let key_bindings = KeyBindings::default();
-        let key_pressed = key_bindings.left;
+        let direction_held = FacingDirection::Left;
         let (new_rotation_angle, new_facing_direction) = get_new_angle_and_facing_direction_for_pdi(
             player_movement_direction,
             pdi_facing_direction,
-            key_pressed,
-            &key_bindings,
+            direction_held,
         );
         assert_eq!(new_rotation_angle, THREE_PI_OVER_TWO);
         assert_eq!(new_facing_direction, FacingDirection::Left);
@@ -293,13 +408,11 @@ mod test_get_new_angle_and_facing_direction_for_pdi {
     fn test_player_moving_left_up_pressed_pdi_facing_down() {
         let player_movement_direction = MovementDirection::Left;
         let pdi_facing_direction = FacingDirection::Down;
-        let key_bindings = KeyBindings::default();
-        let key_pressed = key_bindings.up;
+        let direction_held = FacingDirection::Up;
         let (new_rotation_angle, new_facing_direction) = get_new_angle_and_facing_direction_for_pdi(
             player_movement_direction,
             pdi_facing_direction,
-            key_pressed,
-            &key_bindings,
+            direction_held,
         );
         assert_eq!(new_rotation_angle, THREE_PI_OVER_TWO);
         assert_eq!(new_facing_direction, FacingDirection::Left);
@@ -309,20 +422,23 @@ mod test_get_new_angle_and_facing_direction_for_pdi {
     fn test_player_moving_left_up_pressed_pdi_facing_left() {
         let player_movement_direction = MovementDirection::Left;
         let pdi_facing_direction = FacingDirection::Left;
-        let key_bindings = KeyBindings::default();
-        let key_pressed = key_bindings.up;
+        let direction_held = FacingDirection::Up;
         let (new_rotation_angle, new_facing_direction) = get_new_angle_and_facing_direction_for_pdi(
             player_movement_direction,
             pdi_facing_direction,
-            key_pressed,
-            &key_bindings,
+            direction_held,
         );
         assert_eq!(new_rotation_angle, ZERO_PI);
         assert_eq!(new_facing_direction, FacingDirection::Left);
     }
 }
 
-fn get_auto_movement_speed(transform: &Transform, delta_seconds: &f32, player: &mut Player) -> f32 {
+fn get_auto_movement_speed(
+    transform: &Transform,
+    delta_seconds: &f32,
+    player: &mut Player,
+    sub_pixel: &mut SubPixel,
+) -> f32 {
     let distance_to_tile;
     match player.movement_direction {
         MovementDirection::Up => {
@@ -360,12 +476,13 @@ fn get_auto_movement_speed(transform: &Transform, delta_seconds: &f32, player: &
         MovementDirection::Neutral => distance_to_tile = 0.0,
     }
     let delta = min(
-        FloatOrd((player.speed * TILE_SIZE * delta_seconds).abs()),
+        FloatOrd(sub_pixel.step((player.speed * TILE_SIZE * delta_seconds).abs())),
         FloatOrd(distance_to_tile),
     )
-    .0 as i32 as f32;
+    .0;
     if approx_eq!(f32, delta, distance_to_tile) {
         player.movement_direction = MovementDirection::Neutral;
+        sub_pixel.clear();
     }
     delta
 }
@@ -383,7 +500,12 @@ mod tests_get_auto_movement_speed {
         let delta_seconds = 0.022913124;
         let transform = Transform::from_translation(Vec3::new(97.0, 55.0, 0.0));
         assert_eq!(
-            get_auto_movement_speed(&transform, &delta_seconds, &mut player),
+            get_auto_movement_speed(
+                &transform,
+                &delta_seconds,
+                &mut player,
+                &mut SubPixel::default()
+            ),
             6.0
         );
         assert_eq!(player.movement_direction, MovementDirection::Up);
@@ -398,7 +520,12 @@ mod tests_get_auto_movement_speed {
         let delta_seconds = 0.022913124;
         let transform = Transform::from_translation(Vec3::new(97.0, 55.0, 0.0));
         assert_eq!(
-            get_auto_movement_speed(&transform, &delta_seconds, &mut player),
+            get_auto_movement_speed(
+                &transform,
+                &delta_seconds,
+                &mut player,
+                &mut SubPixel::default()
+            ),
             6.0
         );
         assert_eq!(player.movement_direction, MovementDirection::Down);
@@ -413,7 +540,12 @@ mod tests_get_auto_movement_speed {
         let delta_seconds = 0.022913124;
         let transform = Transform::from_translation(Vec3::new(97.0, 55.0, 0.0));
         assert_eq!(
-            get_auto_movement_speed(&transform, &delta_seconds, &mut player),
+            get_auto_movement_speed(
+                &transform,
+                &delta_seconds,
+                &mut player,
+                &mut SubPixel::default()
+            ),
             6.0
         );
         assert_eq!(player.movement_direction, MovementDirection::Left);
@@ -428,7 +560,12 @@ mod tests_get_auto_movement_speed {
         let delta_seconds = 0.022913124;
         let transform = Transform::from_translation(Vec3::new(97.0, 55.0, 0.0));
         assert_eq!(
-            get_auto_movement_speed(&transform, &delta_seconds, &mut player),
+            get_auto_movement_speed(
+                &transform,
+                &delta_seconds,
+                &mut player,
+                &mut SubPixel::default()
+            ),
             3.0
         );
         assert_eq!(player.movement_direction, MovementDirection::Neutral);
@@ -443,7 +580,12 @@ mod tests_get_auto_movement_speed {
         let delta_seconds = 0.022913124;
         let transform = Transform::from_translation(Vec3::new(97.0, -295.0, 0.0));
         assert_eq!(
-            get_auto_movement_speed(&transform, &delta_seconds, &mut player),
+            get_auto_movement_speed(
+                &transform,
+                &delta_seconds,
+                &mut player,
+                &mut SubPixel::default()
+            ),
             6.0
         );
         assert_eq!(player.movement_direction, MovementDirection::Up);
@@ -458,77 +600,155 @@ mod tests_get_auto_movement_speed {
         let delta_seconds = 0.022913124;
         let transform = Transform::from_translation(Vec3::new(97.0, -295.0, 0.0));
         assert_eq!(
-            get_auto_movement_speed(&transform, &delta_seconds, &mut player),
+            get_auto_movement_speed(
+                &transform,
+                &delta_seconds,
+                &mut player,
+                &mut SubPixel::default()
+            ),
             5.0
         );
         assert_eq!(player.movement_direction, MovementDirection::Neutral);
     }
 }
 
-type Pte<'a, 'b> = (&'a mut Player, &'b mut Transform, Entity);
-
-fn player_movement(
-    mut player_query: Query<Pte, (With<Player>, Without<Collidable>)>,
-    collidable_query: Query<(&Transform, Entity), With<Collidable>>,
-    keyboard: Res<Input<KeyCode>>,
-    time: Res<Time>,
-    key_bindings: Res<KeyBindings>,
-    hookshot_firing: Res<HookshotFiring>,
-    pause_before_move: ResMut<RotationBeforeMoveTimer>,
-) {
-    if hookshot_firing.0 {
-        return;
-    }
-    if !pause_before_move.finished() {
-        return;
-    }
-    let (mut player, mut transform, entity) = player_query.single_mut();
-
+/// Works out how far a player moves this tick from the held
+/// direction keys, finishing any move onto the next tile once
+/// the keys are released.
+pub fn get_movement_delta(
+    player: &mut Player,
+    transform: &Transform,
+    input: &FrameInput,
+    delta_seconds: f32,
+    sub_pixel: &mut SubPixel,
+) -> Vec3 {
     let mut y_delta = 0.0;
     let mut x_delta = 0.0;
-    if keyboard.pressed(key_bindings.up)
+    if input.up
         & [MovementDirection::Neutral, MovementDirection::Up].contains(&player.movement_direction)
     {
-        y_delta += get_manual_movement_speed(player.speed, time.delta_seconds());
+        y_delta += sub_pixel.step(get_manual_movement_speed(player.speed, delta_seconds));
         player.movement_direction = MovementDirection::Up;
-    } else if keyboard.pressed(key_bindings.down)
+    } else if input.down
         & [MovementDirection::Neutral, MovementDirection::Down].contains(&player.movement_direction)
     {
-        y_delta -= get_manual_movement_speed(player.speed, time.delta_seconds());
+        y_delta -= sub_pixel.step(get_manual_movement_speed(player.speed, delta_seconds));
         player.movement_direction = MovementDirection::Down;
-    } else if keyboard.pressed(key_bindings.left)
+    } else if input.left
         & [MovementDirection::Neutral, MovementDirection::Left].contains(&player.movement_direction)
     {
-        x_delta -= get_manual_movement_speed(player.speed, time.delta_seconds());
+        x_delta -= sub_pixel.step(get_manual_movement_speed(player.speed, delta_seconds));
         player.movement_direction = MovementDirection::Left;
-    } else if keyboard.pressed(key_bindings.right)
+    } else if input.right
         & [MovementDirection::Neutral, MovementDirection::Right]
             .contains(&player.movement_direction)
     {
-        x_delta += get_manual_movement_speed(player.speed, time.delta_seconds());
+        x_delta += sub_pixel.step(get_manual_movement_speed(player.speed, delta_seconds));
         player.movement_direction = MovementDirection::Right;
     } else if player.movement_direction == MovementDirection::Up {
-        y_delta += get_auto_movement_speed(&transform, &time.delta_seconds(), &mut player);
+        y_delta += get_auto_movement_speed(transform, &delta_seconds, player, sub_pixel);
     } else if player.movement_direction == MovementDirection::Down {
-        y_delta -= get_auto_movement_speed(&transform, &time.delta_seconds(), &mut player);
+        y_delta -= get_auto_movement_speed(transform, &delta_seconds, player, sub_pixel);
     } else if player.movement_direction == MovementDirection::Left {
-        x_delta -= get_auto_movement_speed(&transform, &time.delta_seconds(), &mut player);
+        x_delta -= get_auto_movement_speed(transform, &delta_seconds, player, sub_pixel);
     } else if player.movement_direction == MovementDirection::Right {
-        x_delta += get_auto_movement_speed(&transform, &time.delta_seconds(), &mut player);
-    }
-    let target = transform.translation + Vec3::new(x_delta, y_delta, 0.0);
-    let collidable_entity: Vec<(Vec3, u32)> = collidable_query
-        .iter()
-        .map(|(t, e)| (t.translation, e.index()))
-        .collect();
-    if check_collision(
-        &target,
-        &entity.index(),
-        &collidable_entity,
-        Vec2::new(TILE_SIZE, TILE_SIZE),
-    )
-    .is_none()
-    {
+        x_delta += get_auto_movement_speed(transform, &delta_seconds, player, sub_pixel);
+    }
+    Vec3::new(x_delta, y_delta, 0.0)
+}
+
+#[cfg(test)]
+mod test_get_movement_delta {
+    use super::*;
+
+    #[test]
+    fn test_get_movement_delta_held_key_moves_player() {
+        let mut player = Player {
+            speed: 1.0,
+            movement_direction: MovementDirection::Neutral,
+        };
+        let input = FrameInput {
+            left: true,
+            ..Default::default()
+        };
+        let transform = Transform::default();
+        assert_eq!(
+            get_movement_delta(
+                &mut player,
+                &transform,
+                &input,
+                1.0,
+                &mut SubPixel::default()
+            ),
+            Vec3::new(-TILE_SIZE, 0.0, 0.0)
+        );
+        assert_eq!(player.movement_direction, MovementDirection::Left);
+    }
+
+    #[test]
+    fn test_get_movement_delta_other_key_ignored_while_moving() {
+        let mut player = Player {
+            speed: 3.0,
+            movement_direction: MovementDirection::Right,
+        };
+        let input = FrameInput {
+            up: true,
+            ..Default::default()
+        };
+        let delta_seconds = 0.022913124;
+        let transform = Transform::from_translation(Vec3::new(97.0, 55.0, 0.0));
+        assert_eq!(
+            get_movement_delta(
+                &mut player,
+                &transform,
+                &input,
+                delta_seconds,
+                &mut SubPixel::default()
+            ),
+            Vec3::new(3.0, 0.0, 0.0)
+        );
+        assert_eq!(player.movement_direction, MovementDirection::Neutral);
+    }
+}
+
+type Pte<'a, 'b, 'c, 'd, 'e> = (
+    &'a mut Player,
+    &'b mut Transform,
+    &'c Collider,
+    &'d CollisionLayers,
+    &'e mut SubPixel,
+    Entity,
+);
+
+fn player_movement(
+    mut player_query: Query<Pte, With<Player>>,
+    collidable_query: Query<CollidableQuery, Without<Player>>,
+    input_buffer: Res<InputBuffer>,
+    hookshot_firing: Res<HookshotFiring>,
+    pause_before_move: ResMut<RotationBeforeMoveTimer>,
+    current_era: Res<CurrentEra>,
+    grid: Res<CollisionGrid>,
+) {
+    if hookshot_firing.0 {
+        return;
+    }
+    if !pause_before_move.finished() {
+        return;
+    }
+    let (mut player, mut transform, collider, layers, mut sub_pixel, entity) =
+        player_query.single_mut();
+
+    let target = transform.translation
+        + get_movement_delta(
+            &mut player,
+            &transform,
+            input_buffer.input(),
+            TICK_SECONDS,
+            &mut sub_pixel,
+        );
+    let collidable_entity =
+        grid.collidables_near(&collidable_query, target, collider, current_era.current_era);
+    if check_collision(&target, entity, collider, layers.mask, &collidable_entity).is_none() {
         transform.translation = target;
     } else {
         player.movement_direction = MovementDirection::Neutral;
@@ -538,7 +758,7 @@ fn player_movement(
 fn camera_follow(
     player_query: Query<(&Transform, &Player), With<Player>>,
     mut camera_query: Query<&mut Transform, (Without<Player>, With<Camera>)>,
-    time: Res<Time>,
+    mut sub_pixel: Local<SubPixel>,
 ) {
     let (player_transform, player) = player_query.single();
     let mut camera_transform = camera_query.single_mut();
@@ -558,26 +778,34 @@ fn camera_follow(
     match player.movement_direction {
         MovementDirection::Up => {
             if delta_y > TILE_SIZE * 2.0 {
-                camera_new_y +=
-                    get_manual_movement_speed(player.speed * catchup_mult, time.delta_seconds());
+                camera_new_y += sub_pixel.step(get_manual_movement_speed(
+                    player.speed * catchup_mult,
+                    TICK_SECONDS,
+                ));
             }
         }
         MovementDirection::Down => {
             if delta_y > TILE_SIZE * 2.0 {
-                camera_new_y -=
-                    get_manual_movement_speed(player.speed * catchup_mult, time.delta_seconds());
+                camera_new_y -= sub_pixel.step(get_manual_movement_speed(
+                    player.speed * catchup_mult,
+                    TICK_SECONDS,
+                ));
             }
         }
         MovementDirection::Left => {
             if delta_x > TILE_SIZE * 2.0 {
-                camera_new_x -=
-                    get_manual_movement_speed(player.speed * catchup_mult, time.delta_seconds());
+                camera_new_x -= sub_pixel.step(get_manual_movement_speed(
+                    player.speed * catchup_mult,
+                    TICK_SECONDS,
+                ));
             }
         }
         MovementDirection::Right => {
             if delta_x > TILE_SIZE * 2.0 {
-                camera_new_x +=
-                    get_manual_movement_speed(player.speed * catchup_mult, time.delta_seconds());
+                camera_new_x += sub_pixel.step(get_manual_movement_speed(
+                    player.speed * catchup_mult,
+                    TICK_SECONDS,
+                ));
             }
         }
         MovementDirection::Neutral => {}
@@ -613,6 +841,9 @@ fn spawn_player(mut commands: Commands) {
                 speed: PLAYER_SPEED,
                 movement_direction: MovementDirection::Neutral,
             },
+            Collider::circle(TILE_SIZE / 2.0),
+            CollisionLayers::player(),
+            SubPixel::default(),
         ))
         .with_children(|parent| {
             parent.spawn((
@@ -0,0 +1,256 @@
+use bevy::prelude::*;
+use core::time::Duration;
+use std::collections::VecDeque;
+
+use crate::events::EraChangedEvent;
+use crate::*;
+
+pub struct RewindPlugin;
+
+impl Plugin for RewindPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RewindHistory>()
+            .insert_resource(Rewinding(false))
+            .add_fixed_timestep_system(
+                TICK,
+                GAMEPLAY_STAGE,
+                toggle_rewind
+                    .run_if_not(is_editing)
+                    .label("rewindtoggle")
+                    .before("rotation"),
+            )
+            .add_fixed_timestep_system(
+                TICK,
+                GAMEPLAY_STAGE,
+                play_back_history.run_if(is_rewinding).after("rewindtoggle"),
+            )
+            .add_fixed_timestep_system(
+                TICK,
+                LATE_TICK_STAGE,
+                record_history.run_if_not(is_rewinding),
+            );
+    }
+}
+
+/// Number of ticks kept in the rewind buffer.
+const REWIND_CAPACITY: usize = 600;
+
+#[derive(Default, Resource)]
+pub struct Rewinding(pub bool);
+
+pub fn is_rewinding(rewinding: Res<Rewinding>) -> bool {
+    rewinding.0
+}
+
+struct HookshotSnapshot {
+    transform: Transform,
+    hookshot: Hookshot,
+    hit_block: Option<HookshotHitBlock>,
+    /// Where the target being dragged back was, if there is one.
+    target_translation: Option<Vec3>,
+}
+
+/// Everything needed to put the player and their
+/// projectiles back the way they were on one tick.
+struct RewindFrame {
+    era: Era,
+    player_translation: Vec3,
+    movement_direction: MovementDirection,
+    pdi_transform: Transform,
+    facing_direction: FacingDirection,
+    rotation_timer_elapsed: Duration,
+    arrows: Vec<(Transform, Arrow)>,
+    hookshot: Option<HookshotSnapshot>,
+    hookshot_firing: bool,
+}
+
+#[derive(Default, Resource)]
+pub struct RewindHistory {
+    frames: VecDeque<RewindFrame>,
+}
+
+impl RewindHistory {
+    /// Records a tick, dropping the oldest one once the buffer is full.
+    fn push(&mut self, frame: RewindFrame) {
+        if self.frames.len() == REWIND_CAPACITY {
+            self.frames.pop_front();
+        }
+        self.frames.push_back(frame);
+    }
+
+    fn pop(&mut self) -> Option<RewindFrame> {
+        self.frames.pop_back()
+    }
+}
+
+fn toggle_rewind(
+    tick_input: Res<TickInput>,
+    mut rewinding: ResMut<Rewinding>,
+    mut pause_before_move: ResMut<RotationBeforeMoveTimer>,
+) {
+    let is_pressed = tick_input.rewind;
+    if rewinding.0 && !is_pressed {
+        // The restored direction is already committed, so
+        // movement resumes without another rotation pause.
+        let duration = pause_before_move.duration();
+        pause_before_move.set_elapsed(duration);
+    }
+    if rewinding.0 != is_pressed {
+        rewinding.0 = is_pressed;
+    }
+}
+
+fn record_history(
+    player_query: Query<(Entity, &Transform, &Player)>,
+    pdi_query: Query<(&Transform, &FacingDirection), With<PlayerDirectionIndicator>>,
+    arrow_query: Query<(&Transform, &Arrow)>,
+    hookshot_query: Query<(&Transform, &Hookshot, Option<&HookshotHitBlock>)>,
+    target_query: Query<&Transform, Without<Hookshot>>,
+    hookshot_firing: Res<HookshotFiring>,
+    pause_before_move: Res<RotationBeforeMoveTimer>,
+    current_era: Res<CurrentEra>,
+    mut history: ResMut<RewindHistory>,
+) {
+    let (player_entity, player_transform, player) = player_query.single();
+    let (pdi_transform, facing_direction) = pdi_query.single();
+    history.push(RewindFrame {
+        era: current_era.current_era,
+        player_translation: player_transform.translation,
+        movement_direction: player.movement_direction,
+        pdi_transform: *pdi_transform,
+        facing_direction: *facing_direction,
+        rotation_timer_elapsed: pause_before_move.elapsed(),
+        arrows: arrow_query
+            .iter()
+            .map(|(transform, arrow)| (*transform, *arrow))
+            .collect(),
+        hookshot: hookshot_query
+            .iter()
+            .find(|(_, hookshot, _)| hookshot.owner == player_entity)
+            .map(|(transform, hookshot, hit_block)| HookshotSnapshot {
+                transform: *transform,
+                hookshot: *hookshot,
+                hit_block: hit_block.copied(),
+                target_translation: hit_block
+                    .filter(|hit_block| hit_block.pull == PullDirection::TowardOwner)
+                    .and_then(|hit_block| target_query.get(hit_block.block).ok())
+                    .map(|target_transform| target_transform.translation),
+            }),
+        hookshot_firing: hookshot_firing.0,
+    });
+}
+
+/// Steps one recorded tick back in time while the rewind key is held.
+fn play_back_history(
+    mut commands: Commands,
+    mut player_query: Query<
+        (Entity, &mut Transform, &mut Player),
+        Without<PlayerDirectionIndicator>,
+    >,
+    mut pdi_query: Query<
+        (&mut Transform, &mut FacingDirection),
+        (With<PlayerDirectionIndicator>, Without<Player>),
+    >,
+    arrow_query: Query<Entity, With<Arrow>>,
+    hookshot_query: Query<(Entity, &Hookshot)>,
+    mut target_query: Query<
+        &mut Transform,
+        (
+            Without<Player>,
+            Without<PlayerDirectionIndicator>,
+            Without<Hookshot>,
+        ),
+    >,
+    mut hookshot_firing: ResMut<HookshotFiring>,
+    mut pause_before_move: ResMut<RotationBeforeMoveTimer>,
+    mut current_era: ResMut<CurrentEra>,
+    mut era_event_writer: EventWriter<EraChangedEvent>,
+    mut history: ResMut<RewindHistory>,
+) {
+    let Some(frame) = history.pop() else {
+        return;
+    };
+
+    // Rewinding past a trip through time goes back to the era left, so
+    // the player is never put down where that era has something in the way.
+    let previous_era = current_era.current_era;
+    if frame.era != previous_era {
+        current_era.current_era = frame.era;
+        era_event_writer.send(EraChangedEvent::rewound(previous_era, frame.era));
+    }
+
+    let (player_entity, mut player_transform, mut player) = player_query.single_mut();
+    player_transform.translation = frame.player_translation;
+    player.movement_direction = frame.movement_direction;
+
+    let (mut pdi_transform, mut facing_direction) = pdi_query.single_mut();
+    *pdi_transform = frame.pdi_transform;
+    *facing_direction = frame.facing_direction;
+    pause_before_move.set_elapsed(frame.rotation_timer_elapsed);
+
+    for arrow_entity in arrow_query.iter() {
+        commands.entity(arrow_entity).despawn();
+    }
+    for (transform, arrow) in frame.arrows {
+        spawn_arrow(&mut commands, transform, arrow);
+    }
+
+    for (hookshot_entity, hookshot) in hookshot_query.iter() {
+        if hookshot.owner != player_entity {
+            continue;
+        }
+        commands.entity(hookshot_entity).despawn();
+    }
+    if let Some(snapshot) = frame.hookshot {
+        let mut hookshot = spawn_hookshot(&mut commands, snapshot.transform, snapshot.hookshot);
+        if let Some(hit_block) = snapshot.hit_block {
+            hookshot.insert(hit_block);
+            if let (Some(translation), Ok(mut target_transform)) = (
+                snapshot.target_translation,
+                target_query.get_mut(hit_block.block),
+            ) {
+                target_transform.translation = translation;
+            }
+        }
+    }
+    hookshot_firing.0 = frame.hookshot_firing;
+}
+
+#[cfg(test)]
+mod test_rewind_history {
+    use super::*;
+
+    fn frame_at(x: f32) -> RewindFrame {
+        RewindFrame {
+            era: Era::Present,
+            player_translation: Vec3::new(x, 0.0, 0.0),
+            movement_direction: MovementDirection::Neutral,
+            pdi_transform: Transform::default(),
+            facing_direction: FacingDirection::Up,
+            rotation_timer_elapsed: Duration::ZERO,
+            arrows: Vec::new(),
+            hookshot: None,
+            hookshot_firing: false,
+        }
+    }
+
+    #[test]
+    fn test_pop_returns_most_recent_frame() {
+        let mut history = RewindHistory::default();
+        history.push(frame_at(1.0));
+        history.push(frame_at(2.0));
+        assert_eq!(history.pop().unwrap().player_translation.x, 2.0);
+        assert_eq!(history.pop().unwrap().player_translation.x, 1.0);
+        assert!(history.pop().is_none());
+    }
+
+    #[test]
+    fn test_push_drops_oldest_frame_when_full() {
+        let mut history = RewindHistory::default();
+        for i in 0..=REWIND_CAPACITY {
+            history.push(frame_at(i as f32));
+        }
+        assert_eq!(history.frames.len(), REWIND_CAPACITY);
+        assert_eq!(history.frames.front().unwrap().player_translation.x, 1.0);
+    }
+}
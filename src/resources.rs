@@ -1,11 +1,106 @@
 use bevy::prelude::*;
+use bevy::reflect::{DynamicEnum, DynamicVariant, TypeInfo, Typed};
+use bevy::utils::HashMap;
+use std::fmt;
+
+use crate::collisionsmod::components::{exists_in_era, InEras};
+use crate::playermod::player::{FacingDirection, MovementDirection};
+
+/// Starts a comment line in the level, controls and recording files.
+pub const COMMENT: char = ';';
+
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Era {
+    Past = 0,
+    Present = 1,
+    Future = 2,
+}
+
+impl Era {
+    pub const ALL: [Era; 3] = [Era::Past, Era::Present, Era::Future];
+
+    /// The era directly after this one, if any.
+    pub fn next(&self) -> Option<Era> {
+        match self {
+            Era::Past => Some(Era::Present),
+            Era::Present => Some(Era::Future),
+            Era::Future => None,
+        }
+    }
+
+    /// The era directly before this one, if any.
+    pub fn previous(&self) -> Option<Era> {
+        match self {
+            Era::Past => None,
+            Era::Present => Some(Era::Past),
+            Era::Future => Some(Era::Present),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_era {
+    use super::*;
+
+    #[test]
+    fn test_era_next() {
+        assert_eq!(Era::Past.next(), Some(Era::Present));
+        assert_eq!(Era::Present.next(), Some(Era::Future));
+        assert_eq!(Era::Future.next(), None);
+    }
+
+    #[test]
+    fn test_era_previous() {
+        assert_eq!(Era::Past.previous(), None);
+        assert_eq!(Era::Present.previous(), Some(Era::Past));
+        assert_eq!(Era::Future.previous(), Some(Era::Present));
+    }
+}
 
 #[derive(Resource)]
 pub struct CurrentEra {
-    pub current_era: Entity,
+    pub current_era: Era,
+    pub era_roots: HashMap<Era, Entity>,
 }
 
-#[derive(Resource)]
+impl CurrentEra {
+    pub fn new(current_era: Era, era_roots: HashMap<Era, Entity>) -> Self {
+        Self {
+            current_era,
+            era_roots,
+        }
+    }
+
+    pub fn root(&self, era: Era) -> Option<Entity> {
+        self.era_roots.get(&era).copied()
+    }
+
+    /// Whether an entity with the given era membership
+    /// exists in the current era.
+    pub fn is_present(&self, in_eras: Option<&InEras>) -> bool {
+        exists_in_era(in_eras, self.current_era)
+    }
+}
+
+#[cfg(test)]
+mod test_current_era {
+    use super::*;
+
+    #[test]
+    fn test_is_present_without_era_membership() {
+        let current_era = CurrentEra::new(Era::Past, HashMap::default());
+        assert!(current_era.is_present(None));
+    }
+
+    #[test]
+    fn test_is_present_with_era_membership() {
+        let current_era = CurrentEra::new(Era::Past, HashMap::default());
+        assert!(current_era.is_present(Some(&InEras(vec![Era::Past, Era::Future]))));
+        assert!(!current_era.is_present(Some(&InEras(vec![Era::Present]))));
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Resource)]
 pub struct KeyBindings {
     pub up: KeyCode,
     pub down: KeyCode,
@@ -13,7 +108,14 @@ pub struct KeyBindings {
     pub right: KeyCode,
     pub interact: KeyCode,
     pub fire: KeyCode,
-    pub hookshot: KeyCode
+    pub hookshot: KeyCode,
+    pub rewind: KeyCode,
+    pub echo: KeyCode,
+    pub travel_forward: KeyCode,
+    pub travel_back: KeyCode,
+    pub editor: KeyCode,
+    pub save_level: KeyCode,
+    pub settings: KeyCode,
 }
 
 impl Default for KeyBindings {
@@ -26,6 +128,754 @@ impl Default for KeyBindings {
             interact: KeyCode::I,
             fire: KeyCode::J,
             hookshot: KeyCode::H,
+            rewind: KeyCode::R,
+            echo: KeyCode::E,
+            travel_forward: KeyCode::T,
+            travel_back: KeyCode::G,
+            editor: KeyCode::F1,
+            save_level: KeyCode::F5,
+            settings: KeyCode::F2,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum KeyBindingsError {
+    MissingKey {
+        line: usize,
+    },
+    UnknownAction {
+        line: usize,
+        name: String,
+    },
+    UnknownKey {
+        line: usize,
+        name: String,
+    },
+    DuplicateKey {
+        key: KeyCode,
+        actions: (&'static str, &'static str),
+    },
+}
+
+impl fmt::Display for KeyBindingsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            KeyBindingsError::MissingKey { line } => {
+                write!(f, "line {}: expected an action = key binding", line)
+            }
+            KeyBindingsError::UnknownAction { line, name } => {
+                write!(f, "line {}: unknown action {}", line, name)
+            }
+            KeyBindingsError::UnknownKey { line, name } => {
+                write!(f, "line {}: unknown key {}", line, name)
+            }
+            KeyBindingsError::DuplicateKey { key, actions } => {
+                write!(
+                    f,
+                    "{:?} is bound to both {} and {}",
+                    key, actions.0, actions.1
+                )
+            }
         }
     }
 }
+
+impl std::error::Error for KeyBindingsError {}
+
+/// An action the player can bind a key to.
+#[derive(Clone, Copy)]
+pub struct Action {
+    pub name: &'static str,
+    pub key: fn(&mut KeyBindings) -> &mut KeyCode,
+    /// Its flag on a tick's input, for actions played out in ticks
+    /// rather than acted on straight away every frame.
+    pub flag: Option<fn(&mut FrameInput) -> &mut bool>,
+}
+
+impl Action {
+    const fn tick(
+        name: &'static str,
+        key: fn(&mut KeyBindings) -> &mut KeyCode,
+        flag: fn(&mut FrameInput) -> &mut bool,
+    ) -> Self {
+        Self {
+            name,
+            key,
+            flag: Some(flag),
+        }
+    }
+
+    const fn frame(name: &'static str, key: fn(&mut KeyBindings) -> &mut KeyCode) -> Self {
+        Self {
+            name,
+            key,
+            flag: None,
+        }
+    }
+}
+
+impl KeyBindings {
+    /// Every action, named as in the controls and recording files.
+    pub const ACTIONS: [Action; 14] = [
+        Action::tick(
+            "up",
+            |key_bindings| &mut key_bindings.up,
+            |input| &mut input.up,
+        ),
+        Action::tick(
+            "down",
+            |key_bindings| &mut key_bindings.down,
+            |input| &mut input.down,
+        ),
+        Action::tick(
+            "left",
+            |key_bindings| &mut key_bindings.left,
+            |input| &mut input.left,
+        ),
+        Action::tick(
+            "right",
+            |key_bindings| &mut key_bindings.right,
+            |input| &mut input.right,
+        ),
+        Action::tick(
+            "interact",
+            |key_bindings| &mut key_bindings.interact,
+            |input| &mut input.interact,
+        ),
+        Action::tick(
+            "fire",
+            |key_bindings| &mut key_bindings.fire,
+            |input| &mut input.fire,
+        ),
+        Action::tick(
+            "hookshot",
+            |key_bindings| &mut key_bindings.hookshot,
+            |input| &mut input.hookshot,
+        ),
+        Action::tick(
+            "rewind",
+            |key_bindings| &mut key_bindings.rewind,
+            |input| &mut input.rewind,
+        ),
+        Action::tick(
+            "echo",
+            |key_bindings| &mut key_bindings.echo,
+            |input| &mut input.echo,
+        ),
+        Action::tick(
+            "travel_forward",
+            |key_bindings| &mut key_bindings.travel_forward,
+            |input| &mut input.travel_forward,
+        ),
+        Action::tick(
+            "travel_back",
+            |key_bindings| &mut key_bindings.travel_back,
+            |input| &mut input.travel_back,
+        ),
+        Action::frame("editor", |key_bindings| &mut key_bindings.editor),
+        Action::frame("save_level", |key_bindings| &mut key_bindings.save_level),
+        Action::frame("settings", |key_bindings| &mut key_bindings.settings),
+    ];
+
+    /// Reads `action = key` lines, with keys named as in [`KeyCode`].
+    /// Actions left out keep their default binding.
+    pub fn parse(source: &str) -> Result<KeyBindings, KeyBindingsError> {
+        let mut key_bindings = KeyBindings::default();
+        for (index, line) in source.lines().enumerate() {
+            let line_number = index + 1;
+            if line.starts_with(COMMENT) || line.trim().is_empty() {
+                continue;
+            }
+            let (name, key_name) = line
+                .split_once('=')
+                .ok_or(KeyBindingsError::MissingKey { line: line_number })?;
+            let (name, key_name) = (name.trim(), key_name.trim());
+            let action = KeyBindings::ACTIONS
+                .iter()
+                .find(|action| action.name == name)
+                .ok_or_else(|| KeyBindingsError::UnknownAction {
+                    line: line_number,
+                    name: name.to_string(),
+                })?;
+            *(action.key)(&mut key_bindings) =
+                key_from_name(key_name).ok_or_else(|| KeyBindingsError::UnknownKey {
+                    line: line_number,
+                    name: key_name.to_string(),
+                })?;
+        }
+        key_bindings.validate()?;
+        Ok(key_bindings)
+    }
+
+    /// Each action with the key bound to it.
+    pub fn keys(&self) -> impl Iterator<Item = (&'static str, KeyCode)> {
+        let mut key_bindings = *self;
+        KeyBindings::ACTIONS
+            .into_iter()
+            .map(move |action| (action.name, *(action.key)(&mut key_bindings)))
+    }
+
+    /// Binds the action at an index of [`KeyBindings::ACTIONS`] to a key,
+    /// unless another action already uses that key.
+    pub fn rebind(&mut self, action: usize, key: KeyCode) -> Result<(), KeyBindingsError> {
+        let Action {
+            name, key: binding, ..
+        } = KeyBindings::ACTIONS[action];
+        if let Some((other_action, _)) = self
+            .keys()
+            .find(|(other_action, other_key)| *other_key == key && *other_action != name)
+        {
+            return Err(KeyBindingsError::DuplicateKey {
+                key,
+                actions: (other_action, name),
+            });
+        }
+        *binding(self) = key;
+        Ok(())
+    }
+
+    /// Fails on the first key bound to more than one action.
+    pub fn validate(&self) -> Result<(), KeyBindingsError> {
+        let keys: Vec<_> = self.keys().collect();
+        for (index, (action, key)) in keys.iter().enumerate() {
+            if let Some((other_action, _)) = keys[index + 1..]
+                .iter()
+                .find(|(_, other_key)| other_key == key)
+            {
+                return Err(KeyBindingsError::DuplicateKey {
+                    key: *key,
+                    actions: (action, other_action),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Writes the bindings back out in the form [`KeyBindings::parse`] reads.
+impl fmt::Display for KeyBindings {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "; Controls. Each line binds an action to a key.")?;
+        for (name, key) in self.keys() {
+            writeln!(f, "{} = {:?}", name, key)?;
+        }
+        Ok(())
+    }
+}
+
+/// Finds a key by its [`KeyCode`] variant name, such as `W` or `Space`.
+fn key_from_name(name: &str) -> Option<KeyCode> {
+    let TypeInfo::Enum(key_info) = KeyCode::type_info() else {
+        return None;
+    };
+    if !key_info.contains_variant(name) {
+        return None;
+    }
+    KeyCode::from_reflect(&DynamicEnum::new("KeyCode", name, DynamicVariant::Unit))
+}
+
+#[cfg(test)]
+mod test_key_bindings {
+    use super::*;
+
+    #[test]
+    fn test_display_round_trip() {
+        let key_bindings = KeyBindings {
+            up: KeyCode::Comma,
+            travel_forward: KeyCode::Space,
+            ..Default::default()
+        };
+        assert_eq!(
+            KeyBindings::parse(&key_bindings.to_string()),
+            Ok(key_bindings)
+        );
+    }
+
+    #[test]
+    fn test_parse_keeps_defaults_for_missing_actions() {
+        let key_bindings = KeyBindings::parse("; Dvorak\nup = Comma\ndown = O\n").unwrap();
+        assert_eq!(key_bindings.up, KeyCode::Comma);
+        assert_eq!(key_bindings.down, KeyCode::O);
+        assert_eq!(key_bindings.fire, KeyBindings::default().fire);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            KeyBindings::parse("up W\n"),
+            Err(KeyBindingsError::MissingKey { line: 1 })
+        );
+        assert_eq!(
+            KeyBindings::parse("jump = Space\n"),
+            Err(KeyBindingsError::UnknownAction {
+                line: 1,
+                name: "jump".to_string()
+            })
+        );
+        assert_eq!(
+            KeyBindings::parse("\nup = Wubble\n"),
+            Err(KeyBindingsError::UnknownKey {
+                line: 2,
+                name: "Wubble".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_rebind_refuses_keys_in_use() {
+        let mut key_bindings = KeyBindings::default();
+        assert_eq!(key_bindings.rebind(0, KeyCode::Up), Ok(()));
+        assert_eq!(key_bindings.rebind(0, KeyCode::Up), Ok(()));
+        assert_eq!(key_bindings.up, KeyCode::Up);
+        assert_eq!(
+            key_bindings.rebind(5, KeyCode::H),
+            Err(KeyBindingsError::DuplicateKey {
+                key: KeyCode::H,
+                actions: ("hookshot", "fire")
+            })
+        );
+        assert_eq!(key_bindings.fire, KeyCode::J);
+    }
+
+    #[test]
+    fn test_parse_rejects_duplicate_keys() {
+        assert_eq!(
+            KeyBindings::parse("fire = H\n"),
+            Err(KeyBindingsError::DuplicateKey {
+                key: KeyCode::H,
+                actions: ("fire", "hookshot")
+            })
+        );
+    }
+}
+
+/// The gamepad buttons for each action. Movement is always
+/// on the left stick and the d-pad.
+#[derive(Clone, Copy, Debug, PartialEq, Resource)]
+pub struct GamepadBindings {
+    pub interact: GamepadButtonType,
+    pub fire: GamepadButtonType,
+    pub hookshot: GamepadButtonType,
+    pub echo: GamepadButtonType,
+    pub rewind: GamepadButtonType,
+    pub travel_forward: GamepadButtonType,
+    pub travel_back: GamepadButtonType,
+}
+
+impl Default for GamepadBindings {
+    fn default() -> Self {
+        Self {
+            interact: GamepadButtonType::South,
+            fire: GamepadButtonType::West,
+            hookshot: GamepadButtonType::East,
+            echo: GamepadButtonType::North,
+            rewind: GamepadButtonType::LeftTrigger2,
+            travel_forward: GamepadButtonType::RightTrigger,
+            travel_back: GamepadButtonType::LeftTrigger,
+        }
+    }
+}
+
+/// The gameplay actions held or pressed on a single frame,
+/// from the keyboard or any gamepad.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct FrameInput {
+    pub up: bool,
+    pub down: bool,
+    pub left: bool,
+    pub right: bool,
+    pub interact: bool,
+    pub fire: bool,
+    pub hookshot: bool,
+    pub echo: bool,
+    pub rewind: bool,
+    pub travel_forward: bool,
+    pub travel_back: bool,
+}
+
+impl FrameInput {
+    pub fn from_keyboard(keyboard: &Input<KeyCode>, key_bindings: &KeyBindings) -> Self {
+        Self {
+            up: keyboard.pressed(key_bindings.up),
+            down: keyboard.pressed(key_bindings.down),
+            left: keyboard.pressed(key_bindings.left),
+            right: keyboard.pressed(key_bindings.right),
+            interact: keyboard.just_pressed(key_bindings.interact),
+            fire: keyboard.just_pressed(key_bindings.fire),
+            hookshot: keyboard.just_pressed(key_bindings.hookshot),
+            echo: keyboard.just_pressed(key_bindings.echo),
+            rewind: keyboard.pressed(key_bindings.rewind),
+            // Era switches happen as the key comes back up.
+            travel_forward: keyboard.just_released(key_bindings.travel_forward),
+            travel_back: keyboard.just_released(key_bindings.travel_back),
+        }
+    }
+
+    /// Reads a gamepad, which moves with the left stick or the d-pad.
+    pub fn from_gamepad(
+        gamepad: Gamepad,
+        buttons: &Input<GamepadButton>,
+        axes: &Axis<GamepadAxis>,
+        gamepad_bindings: &GamepadBindings,
+    ) -> Self {
+        let button = |button_type| GamepadButton::new(gamepad, button_type);
+        let axis = |axis_type| {
+            axes.get(GamepadAxis::new(gamepad, axis_type))
+                .unwrap_or_default()
+        };
+        let stick = stick_direction(
+            axis(GamepadAxisType::LeftStickX),
+            axis(GamepadAxisType::LeftStickY),
+        );
+        let held = |direction, d_pad| stick == Some(direction) || buttons.pressed(button(d_pad));
+        Self {
+            up: held(FacingDirection::Up, GamepadButtonType::DPadUp),
+            down: held(FacingDirection::Down, GamepadButtonType::DPadDown),
+            left: held(FacingDirection::Left, GamepadButtonType::DPadLeft),
+            right: held(FacingDirection::Right, GamepadButtonType::DPadRight),
+            interact: buttons.just_pressed(button(gamepad_bindings.interact)),
+            fire: buttons.just_pressed(button(gamepad_bindings.fire)),
+            hookshot: buttons.just_pressed(button(gamepad_bindings.hookshot)),
+            echo: buttons.just_pressed(button(gamepad_bindings.echo)),
+            rewind: buttons.pressed(button(gamepad_bindings.rewind)),
+            travel_forward: buttons.just_released(button(gamepad_bindings.travel_forward)),
+            travel_back: buttons.just_released(button(gamepad_bindings.travel_back)),
+        }
+    }
+
+    /// The actions of either input, for when several devices are in use.
+    pub fn combine(self, other: FrameInput) -> Self {
+        Self {
+            up: self.up || other.up,
+            down: self.down || other.down,
+            left: self.left || other.left,
+            right: self.right || other.right,
+            interact: self.interact || other.interact,
+            fire: self.fire || other.fire,
+            hookshot: self.hookshot || other.hookshot,
+            echo: self.echo || other.echo,
+            rewind: self.rewind || other.rewind,
+            travel_forward: self.travel_forward || other.travel_forward,
+            travel_back: self.travel_back || other.travel_back,
+        }
+    }
+
+    pub fn hold(&mut self, direction: FacingDirection) {
+        match direction {
+            FacingDirection::Up => self.up = true,
+            FacingDirection::Down => self.down = true,
+            FacingDirection::Left => self.left = true,
+            FacingDirection::Right => self.right = true,
+        }
+    }
+
+    /// The held direction, checked in the order up, down, left, right.
+    pub fn held_direction(&self) -> Option<FacingDirection> {
+        if self.up {
+            Some(FacingDirection::Up)
+        } else if self.down {
+            Some(FacingDirection::Down)
+        } else if self.left {
+            Some(FacingDirection::Left)
+        } else if self.right {
+            Some(FacingDirection::Right)
+        } else {
+            None
+        }
+    }
+}
+
+/// How far the stick has to be pushed before it counts as a direction.
+const STICK_DEADZONE: f32 = 0.5;
+
+/// Snaps a stick position to the direction it is pushed furthest in,
+/// preferring vertical on the diagonals like the direction keys do.
+pub fn stick_direction(x: f32, y: f32) -> Option<FacingDirection> {
+    if x.abs().max(y.abs()) < STICK_DEADZONE {
+        None
+    } else if y.abs() >= x.abs() {
+        Some(if y > 0.0 {
+            FacingDirection::Up
+        } else {
+            FacingDirection::Down
+        })
+    } else if x > 0.0 {
+        Some(FacingDirection::Right)
+    } else {
+        Some(FacingDirection::Left)
+    }
+}
+
+#[cfg(test)]
+mod test_frame_input {
+    use super::*;
+
+    #[test]
+    fn test_held_direction_none() {
+        assert_eq!(FrameInput::default().held_direction(), None);
+    }
+
+    #[test]
+    fn test_held_direction_prefers_vertical() {
+        let input = FrameInput {
+            down: true,
+            left: true,
+            ..Default::default()
+        };
+        assert_eq!(input.held_direction(), Some(FacingDirection::Down));
+    }
+
+    #[test]
+    fn test_stick_direction() {
+        assert_eq!(stick_direction(0.2, -0.3), None);
+        assert_eq!(stick_direction(0.9, 0.4), Some(FacingDirection::Right));
+        assert_eq!(stick_direction(-0.6, 0.1), Some(FacingDirection::Left));
+        assert_eq!(stick_direction(0.7, -0.7), Some(FacingDirection::Down));
+        assert_eq!(stick_direction(-0.3, 0.8), Some(FacingDirection::Up));
+    }
+
+    #[test]
+    fn test_combine_keeps_actions_of_both() {
+        let keyboard = FrameInput {
+            up: true,
+            fire: true,
+            ..Default::default()
+        };
+        let gamepad = FrameInput {
+            right: true,
+            hookshot: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            keyboard.combine(gamepad),
+            FrameInput {
+                up: true,
+                right: true,
+                fire: true,
+                hookshot: true,
+                ..Default::default()
+            }
+        );
+    }
+}
+
+/// The gameplay input seen by the next tick. Held keys follow the latest
+/// frame, while presses are kept until a tick has seen them, so every
+/// press lands on exactly one tick however many frames pass between ticks.
+#[derive(Default, Deref, Resource)]
+pub struct TickInput(FrameInput);
+
+impl TickInput {
+    pub fn latch(&mut self, frame_input: FrameInput) {
+        self.0 = FrameInput {
+            interact: self.interact || frame_input.interact,
+            fire: self.fire || frame_input.fire,
+            hookshot: self.hookshot || frame_input.hookshot,
+            echo: self.echo || frame_input.echo,
+            travel_forward: self.travel_forward || frame_input.travel_forward,
+            travel_back: self.travel_back || frame_input.travel_back,
+            ..frame_input
+        };
+    }
+
+    /// Forgets the presses once a tick has seen them.
+    pub fn consume(&mut self) {
+        self.0 = FrameInput {
+            interact: false,
+            fire: false,
+            hookshot: false,
+            echo: false,
+            travel_forward: false,
+            travel_back: false,
+            ..self.0
+        };
+    }
+}
+
+#[cfg(test)]
+mod test_tick_input {
+    use super::*;
+
+    #[test]
+    fn test_latch_keeps_press_until_consumed() {
+        let mut tick_input = TickInput::default();
+        tick_input.latch(FrameInput {
+            fire: true,
+            up: true,
+            ..Default::default()
+        });
+        tick_input.latch(FrameInput::default());
+        assert!(tick_input.fire);
+        assert!(!tick_input.up);
+        tick_input.consume();
+        assert!(!tick_input.fire);
+    }
+
+    #[test]
+    fn test_consume_keeps_held_keys() {
+        let mut tick_input = TickInput::default();
+        tick_input.latch(FrameInput {
+            left: true,
+            rewind: true,
+            hookshot: true,
+            ..Default::default()
+        });
+        tick_input.consume();
+        assert_eq!(
+            *tick_input,
+            FrameInput {
+                left: true,
+                rewind: true,
+                ..Default::default()
+            }
+        );
+    }
+}
+
+/// How long a press is kept waiting for the moment it can be acted on.
+const BUFFER_TICKS: u32 = 12;
+
+/// A mover's input with the presses it couldn't act on yet added back.
+/// A direction pressed while moving another way is held for the mover
+/// once it stops on a tile, until it sets off that way. Fire, hookshot
+/// and interact presses are kept until they are acted on. Anything
+/// waiting longer than [`BUFFER_TICKS`] is dropped.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Resource)]
+pub struct InputBuffer {
+    input: FrameInput,
+    direction: Option<FacingDirection>,
+    direction_ticks: u32,
+    is_direction_released: bool,
+    interact_ticks: u32,
+    fire_ticks: u32,
+    hookshot_ticks: u32,
+}
+
+impl InputBuffer {
+    /// Takes in a tick's input, buffering whatever was pressed on it.
+    pub fn update(&mut self, tick_input: &FrameInput, movement_direction: MovementDirection) {
+        let mut input = *tick_input;
+        let is_moving = movement_direction != MovementDirection::Neutral;
+        match (input.held_direction(), self.direction) {
+            (Some(direction), _)
+                if is_moving && direction.movement_direction() != movement_direction =>
+            {
+                self.direction = Some(direction);
+                self.direction_ticks = BUFFER_TICKS;
+                self.is_direction_released = false;
+            }
+            (Some(_), _) => self.direction = None,
+            (None, Some(direction)) => {
+                if !is_moving {
+                    // The wait starts over once the mover can act on it,
+                    // so that it outlasts turning to face the new way.
+                    if !self.is_direction_released {
+                        self.is_direction_released = true;
+                        self.direction_ticks = BUFFER_TICKS;
+                    }
+                    input.hold(direction);
+                } else if direction.movement_direction() == movement_direction {
+                    self.direction = None;
+                }
+                self.direction_ticks = self.direction_ticks.saturating_sub(1);
+                if self.direction_ticks == 0 {
+                    self.direction = None;
+                }
+            }
+            (None, None) => {}
+        }
+        input.interact = buffer_press(&mut self.interact_ticks, input.interact);
+        input.fire = buffer_press(&mut self.fire_ticks, input.fire);
+        input.hookshot = buffer_press(&mut self.hookshot_ticks, input.hookshot);
+        self.input = input;
+    }
+
+    /// The input to act on this tick.
+    pub fn input(&self) -> &FrameInput {
+        &self.input
+    }
+
+    pub fn interacted(&mut self) {
+        self.interact_ticks = 0;
+    }
+
+    pub fn fired(&mut self) {
+        self.fire_ticks = 0;
+    }
+
+    pub fn fired_hookshot(&mut self) {
+        self.hookshot_ticks = 0;
+    }
+}
+
+/// Restarts the wait on a press, or counts it down,
+/// returning whether the press is still waiting.
+fn buffer_press(ticks_left: &mut u32, is_pressed: bool) -> bool {
+    *ticks_left = if is_pressed {
+        BUFFER_TICKS
+    } else {
+        ticks_left.saturating_sub(1)
+    };
+    *ticks_left > 0
+}
+
+#[cfg(test)]
+mod test_input_buffer {
+    use super::*;
+
+    #[test]
+    fn test_direction_is_held_from_stopping_until_setting_off() {
+        let mut buffer = InputBuffer::default();
+        let up = FrameInput {
+            up: true,
+            ..Default::default()
+        };
+        buffer.update(&up, MovementDirection::Right);
+        buffer.update(&FrameInput::default(), MovementDirection::Right);
+        assert_eq!(buffer.input().held_direction(), None);
+
+        for _ in 0..BUFFER_TICKS - 1 {
+            buffer.update(&FrameInput::default(), MovementDirection::Neutral);
+            assert_eq!(buffer.input().held_direction(), Some(FacingDirection::Up));
+        }
+        buffer.update(&FrameInput::default(), MovementDirection::Up);
+        buffer.update(&FrameInput::default(), MovementDirection::Neutral);
+        assert_eq!(buffer.input().held_direction(), None);
+    }
+
+    #[test]
+    fn test_direction_is_dropped_after_waiting_too_long() {
+        let mut buffer = InputBuffer::default();
+        let left = FrameInput {
+            left: true,
+            ..Default::default()
+        };
+        buffer.update(&left, MovementDirection::Down);
+        for _ in 0..BUFFER_TICKS {
+            buffer.update(&FrameInput::default(), MovementDirection::Down);
+        }
+        buffer.update(&FrameInput::default(), MovementDirection::Neutral);
+        assert_eq!(buffer.input().held_direction(), None);
+    }
+
+    #[test]
+    fn test_press_waits_until_acted_on() {
+        let mut buffer = InputBuffer::default();
+        let fire = FrameInput {
+            fire: true,
+            ..Default::default()
+        };
+        buffer.update(&fire, MovementDirection::Neutral);
+        buffer.update(&FrameInput::default(), MovementDirection::Neutral);
+        assert!(buffer.input().fire);
+
+        buffer.fired();
+        buffer.update(&FrameInput::default(), MovementDirection::Neutral);
+        assert!(!buffer.input().fire);
+    }
+}
+
+/// How many ticks have run since the level first spawned.
+#[derive(Default, Resource)]
+pub struct TickCount(pub u64);
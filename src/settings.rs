@@ -0,0 +1,148 @@
+use bevy_inspector_egui::bevy_egui::{egui, EguiContext, EguiPlugin};
+
+use crate::resources::KeyBindingsError;
+use crate::*;
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SettingsMenu>()
+            .add_system(toggle_settings.label("settingstoggle"))
+            .add_system(
+                remap_controls
+                    .run_if(is_in_settings)
+                    .after("settingstoggle"),
+            );
+    }
+}
+
+/// Draws the settings menu. Needs a window and the renderer,
+/// unlike [`SettingsPlugin`] which runs the menu itself.
+pub struct SettingsUiPlugin;
+
+impl Plugin for SettingsUiPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(EguiPlugin)
+            .add_system(draw_settings.run_if(is_in_settings).after("settingstoggle"));
+    }
+}
+
+/// The controls screen, which lists every action with its key. The arrow
+/// keys pick an action and return waits for the key to bind it to, which
+/// backspace cancels. Gameplay is paused while it is open.
+#[derive(Default, Resource)]
+pub struct SettingsMenu {
+    pub is_open: bool,
+    /// Index into [`KeyBindings::ACTIONS`].
+    pub selected: usize,
+    pub is_listening: bool,
+    pub conflict: Option<KeyBindingsError>,
+    /// Whether gameplay was ticking when the menu opened, so closing it
+    /// doesn't start ticks before the level has spawned.
+    pub was_ticking: bool,
+}
+
+pub fn is_in_settings(settings_menu: Res<SettingsMenu>) -> bool {
+    settings_menu.is_open
+}
+
+/// Opens or closes the settings, stopping ticks while they are open.
+/// They can't be opened from the level editor.
+fn toggle_settings(
+    keyboard: Res<Input<KeyCode>>,
+    key_bindings: Res<KeyBindings>,
+    editing: Res<Editing>,
+    mut settings_menu: ResMut<SettingsMenu>,
+    mut timesteps: ResMut<FixedTimesteps>,
+) {
+    if !keyboard.just_pressed(key_bindings.settings) || settings_menu.is_listening || editing.0 {
+        return;
+    }
+    if settings_menu.is_open {
+        if settings_menu.was_ticking {
+            start_ticking(&mut timesteps);
+        }
+        *settings_menu = SettingsMenu::default();
+    } else {
+        settings_menu.is_open = true;
+        if let Some(tick) = timesteps.get_mut(TICK) {
+            settings_menu.was_ticking = !tick.paused;
+            tick.pause();
+        }
+    }
+}
+
+fn remap_controls(
+    keyboard: Res<Input<KeyCode>>,
+    mut key_bindings: ResMut<KeyBindings>,
+    mut settings_menu: ResMut<SettingsMenu>,
+) {
+    if settings_menu.is_listening {
+        let Some(key) = keyboard.get_just_pressed().next().copied() else {
+            return;
+        };
+        if key == KeyCode::Back {
+            settings_menu.is_listening = false;
+            settings_menu.conflict = None;
+            return;
+        }
+        let mut rebound = *key_bindings;
+        match rebound.rebind(settings_menu.selected, key) {
+            Ok(()) => {
+                *key_bindings = rebound;
+                settings_menu.is_listening = false;
+                settings_menu.conflict = None;
+            }
+            Err(conflict) => settings_menu.conflict = Some(conflict),
+        }
+        return;
+    }
+
+    let action_count = KeyBindings::ACTIONS.len();
+    if keyboard.just_pressed(KeyCode::Up) {
+        settings_menu.selected = (settings_menu.selected + action_count - 1) % action_count;
+    } else if keyboard.just_pressed(KeyCode::Down) {
+        settings_menu.selected = (settings_menu.selected + 1) % action_count;
+    } else if keyboard.just_pressed(KeyCode::Return) {
+        settings_menu.is_listening = true;
+    }
+}
+
+fn draw_settings(
+    mut egui_context: ResMut<EguiContext>,
+    key_bindings: Res<KeyBindings>,
+    mut settings_menu: ResMut<SettingsMenu>,
+) {
+    egui::Window::new("Controls")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+        .show(egui_context.ctx_mut(), |ui| {
+            egui::Grid::new("controls").show(ui, |ui| {
+                for (index, (action, key)) in key_bindings.keys().enumerate() {
+                    let is_selected = index == settings_menu.selected;
+                    if ui
+                        .selectable_label(is_selected, action.replace('_', " "))
+                        .clicked()
+                    {
+                        settings_menu.selected = index;
+                        settings_menu.is_listening = true;
+                    }
+                    if is_selected && settings_menu.is_listening {
+                        ui.label("press a key");
+                    } else {
+                        ui.label(format!("{:?}", key));
+                    }
+                    ui.end_row();
+                }
+            });
+            if let Some(conflict) = &settings_menu.conflict {
+                ui.colored_label(egui::Color32::RED, conflict.to_string());
+            }
+            ui.label(format!(
+                "Up and down to choose, return to rebind, backspace to cancel, {:?} to close",
+                key_bindings.settings
+            ));
+        });
+}
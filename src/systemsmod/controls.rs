@@ -0,0 +1,56 @@
+use bevy::asset::FileAssetIo;
+use std::path::PathBuf;
+
+use crate::*;
+
+pub struct ControlsPlugin;
+
+impl Plugin for ControlsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system_to_stage(StartupStage::PreStartup, load_key_bindings)
+            .add_system(save_key_bindings.run_if_resource_exists::<ControlsFile>());
+    }
+}
+
+/// The user's controls, next to the assets folder.
+const CONTROLS_FILE: &str = "controls.cfg";
+
+/// Where changes to the key bindings are saved.
+#[derive(Resource)]
+pub struct ControlsFile(PathBuf);
+
+/// Reads the key bindings from the controls file, falling back to the
+/// defaults if it is missing or invalid. Bindings put in place before
+/// startup are kept, and never saved.
+fn load_key_bindings(mut commands: Commands, key_bindings: Option<Res<KeyBindings>>) {
+    if key_bindings.is_some() {
+        return;
+    }
+    let path = FileAssetIo::get_base_path().join(CONTROLS_FILE);
+    let key_bindings = match std::fs::read_to_string(&path) {
+        Ok(source) => KeyBindings::parse(&source).unwrap_or_else(|error| {
+            warn!("Using default controls, {}: {}", path.display(), error);
+            KeyBindings::default()
+        }),
+        Err(_) => KeyBindings::default(),
+    };
+    commands.insert_resource(key_bindings);
+    commands.insert_resource(ControlsFile(path));
+}
+
+/// Writes the key bindings back whenever they change,
+/// unless two actions now share a key.
+fn save_key_bindings(key_bindings: Res<KeyBindings>, controls_file: Res<ControlsFile>) {
+    if !key_bindings.is_changed() || key_bindings.is_added() {
+        return;
+    }
+    if let Err(error) = key_bindings.validate() {
+        warn!("Not saving controls: {}", error);
+        return;
+    }
+    let path = &controls_file.0;
+    match std::fs::write(path, key_bindings.to_string()) {
+        Ok(()) => info!("Saved controls to {}", path.display()),
+        Err(error) => error!("Could not save controls to {}: {}", path.display(), error),
+    }
+}
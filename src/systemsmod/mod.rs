@@ -1,12 +1,19 @@
 use crate::*;
 use bevy::app::PluginGroupBuilder;
 
+pub mod controls;
 pub mod general_systems;
+pub mod recording;
+pub mod tick;
 
 pub struct SystemsModPluginGroup;
 
 impl PluginGroup for SystemsModPluginGroup {
     fn build(self) -> PluginGroupBuilder {
-        PluginGroupBuilder::start::<Self>().add(GeneralSystemsPlugin)
+        PluginGroupBuilder::start::<Self>()
+            .add(GeneralSystemsPlugin)
+            .add(ControlsPlugin)
+            .add(TickPlugin)
+            .add(RecordingPlugin)
     }
 }
@@ -0,0 +1,313 @@
+use bevy::utils::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{LineWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::resources::COMMENT;
+use crate::*;
+
+pub struct RecordingPlugin;
+
+impl Plugin for RecordingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_fixed_timestep_system(
+            TICK,
+            INPUT_STAGE,
+            play_back_input
+                .run_if_resource_exists::<InputPlayback>()
+                .label("inputplayback"),
+        )
+        .add_fixed_timestep_system(
+            TICK,
+            INPUT_STAGE,
+            record_input
+                .run_if_resource_exists::<InputRecorder>()
+                .after("inputplayback"),
+        );
+    }
+}
+
+const HEADER: &str = "\
+; Input recording. Each line is a tick, counted from when the level
+; spawned, followed by the actions held or pressed on that tick.
+; Ticks without any input are left out.
+";
+
+/// Each action in a recording, named as in the controls file,
+/// along with its flag on a tick's input.
+fn tick_actions() -> impl Iterator<Item = (&'static str, fn(&mut FrameInput) -> &mut bool)> {
+    KeyBindings::ACTIONS
+        .into_iter()
+        .filter_map(|action| Some((action.name, action.flag?)))
+}
+
+#[derive(Debug, PartialEq)]
+pub enum RecordingError {
+    UnknownArgument(String),
+    MissingPath(String),
+    Io { path: PathBuf, message: String },
+    BadTick { line: usize },
+    TickOutOfOrder { line: usize },
+    UnknownAction { line: usize, name: String },
+}
+
+impl fmt::Display for RecordingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RecordingError::UnknownArgument(argument) => {
+                write!(
+                    f,
+                    "unknown argument {}, expected --record or --play",
+                    argument
+                )
+            }
+            RecordingError::MissingPath(flag) => write!(f, "{} needs a file to use", flag),
+            RecordingError::Io { path, message } => write!(f, "{}: {}", path.display(), message),
+            RecordingError::BadTick { line } => {
+                write!(f, "line {}: expected a tick number", line)
+            }
+            RecordingError::TickOutOfOrder { line } => {
+                write!(f, "line {}: ticks must be in increasing order", line)
+            }
+            RecordingError::UnknownAction { line, name } => {
+                write!(f, "line {}: unknown action {}", line, name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for RecordingError {}
+
+/// Where the game's per-tick input goes to or comes from.
+#[derive(Debug, PartialEq)]
+pub enum ReplayMode {
+    Live,
+    Record(PathBuf),
+    Play(PathBuf),
+}
+
+impl ReplayMode {
+    /// Reads `--record <file>` or `--play <file>` from the command line.
+    pub fn from_args(mut args: impl Iterator<Item = String>) -> Result<Self, RecordingError> {
+        let Some(flag) = args.next() else {
+            return Ok(ReplayMode::Live);
+        };
+        let mode: fn(PathBuf) -> ReplayMode = match flag.as_str() {
+            "--record" => ReplayMode::Record,
+            "--play" => ReplayMode::Play,
+            _ => return Err(RecordingError::UnknownArgument(flag)),
+        };
+        let path = args.next().ok_or(RecordingError::MissingPath(flag))?;
+        match args.next() {
+            Some(argument) => Err(RecordingError::UnknownArgument(argument)),
+            None => Ok(mode(path.into())),
+        }
+    }
+
+    /// Opens the file to record to, or reads the one to play back.
+    pub fn start(self, app: &mut App) -> Result<(), RecordingError> {
+        match self {
+            ReplayMode::Live => {}
+            ReplayMode::Record(path) => {
+                let mut writer = File::create(&path)
+                    .map(LineWriter::new)
+                    .map_err(|error| io_error(&path, error))?;
+                writer
+                    .write_all(HEADER.as_bytes())
+                    .map_err(|error| io_error(&path, error))?;
+                info!("Recording input to {}", path.display());
+                app.insert_resource(InputRecorder { path, writer });
+            }
+            ReplayMode::Play(path) => {
+                let source =
+                    std::fs::read_to_string(&path).map_err(|error| io_error(&path, error))?;
+                let recording = Recording::parse(&source)?;
+                info!("Playing back input from {}", path.display());
+                app.insert_resource(InputPlayback(recording));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn io_error(path: &Path, error: std::io::Error) -> RecordingError {
+    RecordingError::Io {
+        path: path.to_path_buf(),
+        message: error.to_string(),
+    }
+}
+
+/// The input of every tick of a recording.
+#[derive(Debug, Default, PartialEq)]
+pub struct Recording {
+    inputs: HashMap<u64, FrameInput>,
+    last_tick: Option<u64>,
+}
+
+impl Recording {
+    pub fn parse(source: &str) -> Result<Recording, RecordingError> {
+        let mut recording = Recording::default();
+        for (index, line) in source.lines().enumerate() {
+            let line_number = index + 1;
+            if line.starts_with(COMMENT) || line.trim().is_empty() {
+                continue;
+            }
+            let mut words = line.split_whitespace();
+            let tick = words
+                .next()
+                .and_then(|word| word.parse::<u64>().ok())
+                .ok_or(RecordingError::BadTick { line: line_number })?;
+            if recording
+                .last_tick
+                .is_some_and(|last_tick| tick <= last_tick)
+            {
+                return Err(RecordingError::TickOutOfOrder { line: line_number });
+            }
+            let mut input = FrameInput::default();
+            for name in words {
+                let (_, flag) = tick_actions()
+                    .find(|(action, _)| *action == name)
+                    .ok_or_else(|| RecordingError::UnknownAction {
+                        line: line_number,
+                        name: name.to_string(),
+                    })?;
+                *flag(&mut input) = true;
+            }
+            recording.inputs.insert(tick, input);
+            recording.last_tick = Some(tick);
+        }
+        Ok(recording)
+    }
+
+    /// The input on a tick, which is no input at all
+    /// for ticks that were left out of the recording.
+    pub fn input_on(&self, tick: u64) -> FrameInput {
+        self.inputs.get(&tick).copied().unwrap_or_default()
+    }
+}
+
+/// The line recording a tick's input, unless there was no input.
+pub fn recorded_line(tick: u64, input: &FrameInput) -> Option<String> {
+    let mut input = *input;
+    let names: Vec<&str> = tick_actions()
+        .filter(|(_, flag)| *flag(&mut input))
+        .map(|(name, _)| name)
+        .collect();
+    if names.is_empty() {
+        return None;
+    }
+    Some(format!("{} {}", tick, names.join(" ")))
+}
+
+/// The file the input of every tick is written to as it happens,
+/// so the recording survives the game crashing.
+#[derive(Resource)]
+pub struct InputRecorder {
+    path: PathBuf,
+    writer: LineWriter<File>,
+}
+
+/// A recording fed to the game in place of the keyboard.
+#[derive(Resource)]
+pub struct InputPlayback(pub Recording);
+
+fn play_back_input(
+    playback: Res<InputPlayback>,
+    tick_count: Res<TickCount>,
+    mut tick_input: ResMut<TickInput>,
+) {
+    if playback.0.last_tick.map(|last_tick| last_tick + 1) == Some(tick_count.0) {
+        info!("Input playback finished on tick {}", tick_count.0);
+    }
+    tick_input.latch(playback.0.input_on(tick_count.0));
+}
+
+fn record_input(
+    mut recorder: ResMut<InputRecorder>,
+    tick_count: Res<TickCount>,
+    tick_input: Res<TickInput>,
+) {
+    let Some(line) = recorded_line(tick_count.0, &tick_input) else {
+        return;
+    };
+    if let Err(error) = writeln!(recorder.writer, "{}", line) {
+        error!(
+            "Could not record input to {}: {}",
+            recorder.path.display(),
+            error
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_recording {
+    use super::*;
+
+    #[test]
+    fn test_recorded_lines_parse_back() {
+        let inputs = [
+            FrameInput {
+                right: true,
+                fire: true,
+                ..Default::default()
+            },
+            FrameInput::default(),
+            FrameInput {
+                rewind: true,
+                travel_back: true,
+                ..Default::default()
+            },
+        ];
+        let source: String = inputs
+            .iter()
+            .enumerate()
+            .filter_map(|(tick, input)| recorded_line(tick as u64, input))
+            .map(|line| line + "\n")
+            .collect();
+        assert_eq!(source, "0 right fire\n2 rewind travel_back\n");
+
+        let recording = Recording::parse(&(HEADER.to_string() + &source)).unwrap();
+        for (tick, input) in inputs.iter().enumerate() {
+            assert_eq!(recording.input_on(tick as u64), *input);
+        }
+        assert_eq!(recording.input_on(3), FrameInput::default());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            Recording::parse("1 up\nup\n"),
+            Err(RecordingError::BadTick { line: 2 })
+        );
+        assert_eq!(
+            Recording::parse("3 up\n3 down\n"),
+            Err(RecordingError::TickOutOfOrder { line: 2 })
+        );
+        assert_eq!(
+            Recording::parse("; comment\n1 jump\n"),
+            Err(RecordingError::UnknownAction {
+                line: 2,
+                name: "jump".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_replay_mode_from_args() {
+        let args = |args: &[&str]| ReplayMode::from_args(args.iter().map(|arg| arg.to_string()));
+        assert_eq!(args(&[]), Ok(ReplayMode::Live));
+        assert_eq!(
+            args(&["--play", "bug.input"]),
+            Ok(ReplayMode::Play("bug.input".into()))
+        );
+        assert_eq!(
+            args(&["--record"]),
+            Err(RecordingError::MissingPath("--record".to_string()))
+        );
+        assert_eq!(
+            args(&["--fast"]),
+            Err(RecordingError::UnknownArgument("--fast".to_string()))
+        );
+    }
+}
@@ -0,0 +1,89 @@
+use bevy::input::InputSystem;
+use core::time::Duration;
+
+use crate::*;
+
+pub struct TickPlugin;
+
+impl Plugin for TickPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TickInput>()
+            .init_resource::<GamepadBindings>()
+            .init_resource::<TickCount>()
+            .add_fixed_timestep(TICK_DURATION, TICK)
+            .add_fixed_timestep_child_stage(TICK)
+            .add_fixed_timestep_child_stage(TICK)
+            .add_fixed_timestep_child_stage(TICK)
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                latch_tick_input
+                    .run_unless_resource_exists::<InputPlayback>()
+                    .run_if_not(is_in_settings)
+                    .after(InputSystem),
+            )
+            .add_fixed_timestep_system(TICK, LATE_TICK_STAGE, consume_tick_input)
+            .add_fixed_timestep_system(TICK, LATE_TICK_STAGE, count_tick);
+        // Ticks start once the level has spawned, so that a tick
+        // number means the same moment of play on every run.
+        app.world
+            .resource_mut::<FixedTimesteps>()
+            .get_mut(TICK)
+            .expect("the tick timestep was just added")
+            .pause();
+    }
+}
+
+/// The fixed timestep that all gameplay runs on, so that it plays out
+/// the same however fast frames are rendered.
+pub const TICK: &str = "tick";
+pub const TICKS_PER_SECOND: u64 = 60;
+pub const TICK_DURATION: Duration = Duration::from_nanos(1_000_000_000 / TICKS_PER_SECOND);
+pub const TICK_SECONDS: f32 = 1.0 / TICKS_PER_SECOND as f32;
+
+/// Sub-stages of every tick, in the order they run. Commands are
+/// applied at the end of each one.
+pub const INPUT_STAGE: usize = 0;
+pub const GAMEPLAY_STAGE: usize = 1;
+/// Runs once everything has moved. Systems that react to
+/// `CollisionEvent` go after "collisiondetection".
+pub const COLLISION_STAGE: usize = 2;
+/// Sees the state the tick ended in.
+pub const LATE_TICK_STAGE: usize = 3;
+
+/// Lets ticks run from now on.
+pub fn start_ticking(timesteps: &mut FixedTimesteps) {
+    if let Some(tick) = timesteps.get_mut(TICK) {
+        tick.unpause();
+    }
+}
+
+fn latch_tick_input(
+    keyboard: Res<Input<KeyCode>>,
+    key_bindings: Res<KeyBindings>,
+    gamepads: Res<Gamepads>,
+    buttons: Res<Input<GamepadButton>>,
+    axes: Res<Axis<GamepadAxis>>,
+    gamepad_bindings: Res<GamepadBindings>,
+    mut tick_input: ResMut<TickInput>,
+) {
+    let frame_input = gamepads.iter().fold(
+        FrameInput::from_keyboard(&keyboard, &key_bindings),
+        |frame_input, gamepad| {
+            frame_input.combine(FrameInput::from_gamepad(
+                gamepad,
+                &buttons,
+                &axes,
+                &gamepad_bindings,
+            ))
+        },
+    );
+    tick_input.latch(frame_input);
+}
+
+fn consume_tick_input(mut tick_input: ResMut<TickInput>) {
+    tick_input.consume();
+}
+
+fn count_tick(mut tick_count: ResMut<TickCount>) {
+    tick_count.0 += 1;
+}